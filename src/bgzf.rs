@@ -0,0 +1,94 @@
+use flate2::{write::DeflateEncoder, Compression, Crc};
+
+use std::io::{self, Write};
+
+/// Maximum number of uncompressed bytes in each block.
+///
+/// This is the same as htslib and it ensures that compressed blocks always fit within 64 KiB.
+const MAX_BLOCK_SIZE: usize = 0xff00;
+
+/// Empty block that marks the end of a BGZF file.
+const EOF_BLOCK: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Writer for the BGZF (blocked gzip) format.
+///
+/// BGZF files are a series of concatenated gzip members, so they can be decompressed by any gzip
+/// decoder. Each block is limited in size, which allows for random access with an index.
+///
+/// The end-of-file marker is written when the writer is dropped.
+pub struct BgzfWriter<W: Write> {
+    inner: W,
+    buf: Vec<u8>,
+    level: Compression,
+}
+
+impl<W: Write> BgzfWriter<W> {
+    pub fn new(inner: W, level: Compression) -> Self {
+        Self {
+            inner,
+            buf: Vec::with_capacity(MAX_BLOCK_SIZE),
+            level,
+        }
+    }
+
+    fn write_block(&mut self, len: usize) -> io::Result<()> {
+        let data = &self.buf[..len];
+
+        let mut encoder = DeflateEncoder::new(Vec::with_capacity(len), self.level);
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+
+        let mut crc = Crc::new();
+        crc.update(data);
+
+        // block size minus one, including the 18 byte header and 8 byte footer
+        let block_size = (compressed.len() + 25) as u16;
+
+        let mut header = [
+            0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43,
+            0x02, 0x00, 0x00, 0x00,
+        ];
+        header[16..].copy_from_slice(&block_size.to_le_bytes());
+
+        self.inner.write_all(&header)?;
+        self.inner.write_all(&compressed)?;
+        self.inner.write_all(&crc.sum().to_le_bytes())?;
+        self.inner.write_all(&(len as u32).to_le_bytes())?;
+
+        self.buf.drain(..len);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for BgzfWriter<W> {
+    fn write(&mut self, b: &[u8]) -> io::Result<usize> {
+        let len = b.len().min(MAX_BLOCK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&b[..len]);
+
+        if self.buf.len() >= MAX_BLOCK_SIZE {
+            self.write_block(MAX_BLOCK_SIZE)?;
+        }
+
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() {
+            self.write_block(self.buf.len())?;
+        }
+
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for BgzfWriter<W> {
+    fn drop(&mut self) {
+        // errors are ignored, like when dropping a BufWriter
+        let _ = self.flush();
+        let _ = self.inner.write_all(&EOF_BLOCK);
+        let _ = self.inner.flush();
+    }
+}
@@ -22,6 +22,9 @@ pub enum Error {
     #[error("Unpaired read in {0}")]
     UnpairedRead(String),
 
+    #[error("More than {0} reads were buffered for the other branches while waiting for reads in branch {1}")]
+    BranchBufferFull(usize, usize),
+
     #[error("Error parsing record {idx} in {origin}: {source}")]
     ParseRecord {
         origin: Origin,
@@ -29,6 +32,14 @@ pub enum Error {
         source: Box<dyn std::error::Error>,
     },
 
+    #[error("Record {idx} in {origin} has length {len}, which is longer than the maximum length of {max_len}")]
+    ReadTooLong {
+        origin: Origin,
+        idx: usize,
+        len: usize,
+        max_len: usize,
+    },
+
     #[error("Could not parse \"{string}\" in \"{context}\": {reason}")]
     Parse {
         string: String,
@@ -61,6 +72,18 @@ pub enum NameError {
     Duplicate(Name),
     #[error("Expected {0}, but found {1:?}")]
     Type(&'static str, Data),
+    #[error("No quality scores for {0}")]
+    NoQual(Name),
+    #[error("Expected equal lengths, but found lengths {0} and {1}")]
+    UnequalLen(usize, usize),
+    #[error("Index {0} is out of range for length {1}")]
+    OutOfRange(usize, usize),
+    #[error("Division by zero")]
+    DivisionByZero,
+    #[error("Expected the lower bound {0} to be at most the upper bound {1}")]
+    InvalidBounds(Data, Data),
+    #[error("Quality scores with offset {0} were detected, but found quality score {1:?}")]
+    InconsistentQual(u8, char),
 }
 
 #[derive(Debug)]
@@ -68,6 +91,7 @@ pub enum Name {
     StrType(StrType),
     Label(InlineString),
     Attr(InlineString),
+    Literal(Vec<u8>),
 }
 
 impl fmt::Display for Name {
@@ -77,6 +101,7 @@ impl fmt::Display for Name {
             StrType(str_type) => write!(f, "string type \"{}\"", str_type),
             Label(label) => write!(f, "label \"{}\"", label),
             Attr(attr) => write!(f, "attribute \"{}\"", attr),
+            Literal(literal) => write!(f, "literal \"{}\"", utf8(literal)),
         }
     }
 }
@@ -137,6 +137,14 @@ impl LabelOrAttr {
             LabelOrAttr::Attr(a) => a.label,
         }
     }
+
+    /// Check whether the label or attribute exists in a read.
+    pub fn exists(&self, read: &Read) -> bool {
+        match self {
+            LabelOrAttr::Label(l) => read.mapping(l.str_type, l.label).is_ok(),
+            LabelOrAttr::Attr(a) => read.data(a.str_type, a.label, a.attr).is_ok(),
+        }
+    }
 }
 
 impl From<Label> for LabelOrAttr {
@@ -3,7 +3,20 @@ use crate::expr;
 use crate::parse_utils::*;
 use crate::read::*;
 
-const UNKNOWN_QUAL: u8 = b'I';
+pub(crate) const UNKNOWN_QUAL: u8 = b'I';
+
+/// How to handle quality scores for parts of a format expression that do not have quality scores,
+/// like literals, attributes, padding, or strings without quality scores.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum MissingQual {
+    /// Use the string itself as quality scores for literals and attributes, and a default quality
+    /// score for everything else.
+    Default,
+    /// Fill in with a specific quality score character.
+    Fill(u8),
+    /// Return an error.
+    Error,
+}
 
 #[derive(Debug, Clone)]
 pub struct FormatExpr {
@@ -15,6 +28,9 @@ enum Expr {
     Literal(Vec<u8>),
     LabelOrAttr(expr::LabelOrAttr),
     Repeat(Box<Expr>, Num),
+    Pad(Box<Expr>, usize, u8),
+    TypeOf(expr::Attr),
+    Case(Box<Expr>, bool),
 }
 
 #[derive(Debug, Clone)]
@@ -30,33 +46,89 @@ impl FormatExpr {
     }
 
     pub fn format(&self, read: &Read, use_qual: bool) -> std::result::Result<Vec<u8>, NameError> {
+        if use_qual {
+            self.format_qual(read, MissingQual::Default)
+        } else {
+            let mut res = Vec::new();
+
+            for e in &self.expr {
+                format_expr(read, None, e, &mut res)?;
+            }
+
+            Ok(res)
+        }
+    }
+
+    /// Labels and attributes that must exist in a read to format it.
+    pub fn required_names(&self) -> Vec<expr::LabelOrAttr> {
+        let mut res = Vec::new();
+        self.expr
+            .iter()
+            .for_each(|e| required_names_rec(e, &mut res));
+        res
+    }
+
+    /// Format the quality scores, with `missing` specifying how to handle missing quality scores.
+    pub fn format_qual(
+        &self,
+        read: &Read,
+        missing: MissingQual,
+    ) -> std::result::Result<Vec<u8>, NameError> {
         let mut res = Vec::new();
 
         for e in &self.expr {
-            format_expr(read, use_qual, e, &mut res)?;
+            format_expr(read, Some(missing), e, &mut res)?;
         }
 
         Ok(res)
     }
 }
 
+fn required_names_rec(e: &Expr, res: &mut Vec<expr::LabelOrAttr>) {
+    use Expr::*;
+    match e {
+        Literal(_) => (),
+        LabelOrAttr(l) => res.push(l.clone()),
+        Repeat(expr, num) => {
+            required_names_rec(expr, res);
+            match num {
+                Num::Literal(_) => (),
+                Num::LabelOrAttrLen(l) => res.push(l.clone()),
+                Num::LabelOrAttrCoerce(a) => res.push(a.clone().into()),
+            }
+        }
+        Pad(expr, _, _) => required_names_rec(expr, res),
+        TypeOf(a) => res.push(a.clone().into()),
+        Case(expr, _) => required_names_rec(expr, res),
+    }
+}
+
 fn format_expr(
     read: &Read,
-    use_qual: bool,
+    qual: Option<MissingQual>,
     e: &Expr,
     res: &mut Vec<u8>,
 ) -> std::result::Result<(), NameError> {
     use Expr::*;
     match e {
-        Literal(s) => res.extend(s),
+        Literal(s) => match qual {
+            Some(MissingQual::Fill(c)) => res.extend(s.iter().map(|_| c)),
+            Some(MissingQual::Error) => Err(NameError::NoQual(Name::Literal(s.clone())))?,
+            _ => res.extend(s),
+        },
         LabelOrAttr(l) => match l {
             expr::LabelOrAttr::Label(expr::Label { str_type, label }) => {
-                if use_qual {
-                    if let Some(qual) = read.substring_qual(*str_type, *label)? {
-                        res.extend(qual);
+                if let Some(missing) = qual {
+                    if let Some(q) = read.substring_qual(*str_type, *label)? {
+                        res.extend(q);
                     } else {
                         let len = read.mapping(*str_type, *label)?.len;
-                        res.extend((0..len).map(|_| UNKNOWN_QUAL));
+                        let c = match missing {
+                            MissingQual::Default => UNKNOWN_QUAL,
+                            MissingQual::Fill(c) => c,
+                            MissingQual::Error => Err(NameError::NoQual(Name::StrType(*str_type)))?,
+                        };
+                        res.extend((0..len).map(|_| c));
                     }
                 } else {
                     res.extend(read.substring(*str_type, *label)?);
@@ -67,7 +139,12 @@ fn format_expr(
                 label,
                 attr,
             }) => {
-                res.extend(read.data(*str_type, *label, *attr)?.to_string().as_bytes());
+                let s = read.data(*str_type, *label, *attr)?.to_string();
+                match qual {
+                    Some(MissingQual::Fill(c)) => res.extend(s.bytes().map(|_| c)),
+                    Some(MissingQual::Error) => Err(NameError::NoQual(Name::Attr(*attr)))?,
+                    _ => res.extend(s.as_bytes()),
+                }
             }
         },
         Repeat(expr, num) => {
@@ -92,7 +169,7 @@ fn format_expr(
 
             if repeats >= 1 {
                 let start = res.len();
-                format_expr(read, use_qual, &*expr, res)?;
+                format_expr(read, qual, expr, res)?;
                 let end = res.len();
                 res.reserve((repeats - 1) * (end - start));
 
@@ -101,6 +178,46 @@ fn format_expr(
                 }
             }
         }
+        Pad(expr, width, fill) => {
+            let start = res.len();
+            format_expr(read, qual, expr, res)?;
+            let len = res.len() - start;
+
+            if len < *width {
+                let fill = match qual {
+                    None => *fill,
+                    Some(MissingQual::Default) => UNKNOWN_QUAL,
+                    Some(MissingQual::Fill(c)) => c,
+                    Some(MissingQual::Error) => Err(NameError::NoQual(Name::Literal(vec![*fill])))?,
+                };
+                res.splice(start..start, (0..(*width - len)).map(|_| fill));
+            }
+        }
+        TypeOf(expr::Attr {
+            str_type,
+            label,
+            attr,
+        }) => {
+            let s = read.data(*str_type, *label, *attr)?.type_name();
+            match qual {
+                Some(MissingQual::Fill(c)) => res.extend(s.bytes().map(|_| c)),
+                Some(MissingQual::Error) => Err(NameError::NoQual(Name::Attr(*attr)))?,
+                _ => res.extend(s.as_bytes()),
+            }
+        }
+        Case(expr, upper) => {
+            let start = res.len();
+            format_expr(read, qual, expr, res)?;
+
+            // quality scores are not modified
+            if qual.is_none() {
+                if *upper {
+                    res[start..].make_ascii_uppercase();
+                } else {
+                    res[start..].make_ascii_lowercase();
+                }
+            }
+        }
     }
 
     Ok(())
@@ -137,10 +254,13 @@ fn parse(expr: &[u8]) -> Result<Vec<Expr>> {
 
                 let idx = find_skip_quotes(&curr, b';');
                 let end = idx.unwrap_or(curr.len());
+                let spec_idx = find_skip_quotes(&curr[..end], b':');
                 let left =
-                    trim_ascii_whitespace(&curr[..end]).ok_or_else(|| Error::InvalidName {
-                        string: utf8(&curr[..end]),
-                        context: utf8(expr),
+                    trim_ascii_whitespace(&curr[..spec_idx.unwrap_or(end)]).ok_or_else(|| {
+                        Error::InvalidName {
+                            string: utf8(&curr[..end]),
+                            context: utf8(expr),
+                        }
                     })?;
 
                 let e = if left[0] == b'\'' && left[left.len() - 1] == b'\'' {
@@ -149,6 +269,12 @@ fn parse(expr: &[u8]) -> Result<Vec<Expr>> {
                     Expr::LabelOrAttr(expr::LabelOrAttr::new(left)?)
                 };
 
+                let e = if let Some(spec_idx) = spec_idx {
+                    parse_spec(e, &curr[spec_idx + 1..end], expr)?
+                } else {
+                    e
+                };
+
                 if let Some(idx) = idx {
                     let right = trim_ascii_whitespace(&curr[idx + 1..]).ok_or_else(|| {
                         Error::InvalidName {
@@ -199,3 +325,73 @@ fn parse(expr: &[u8]) -> Result<Vec<Expr>> {
 
     Ok(res)
 }
+
+/// Parse a format spec like `05` (pad to width 5 with zeros) or `5` (pad to width 5 with spaces).
+///
+/// The spec `type` formats the name of the type of an attribute instead of its value, and the
+/// specs `upper` and `lower` convert ASCII letters to uppercase or lowercase.
+fn parse_spec(e: Expr, spec: &[u8], expr: &[u8]) -> Result<Expr> {
+    let spec = trim_ascii_whitespace(spec).unwrap_or(spec);
+
+    match spec {
+        b"upper" => return Ok(Expr::Case(Box::new(e), true)),
+        b"lower" => return Ok(Expr::Case(Box::new(e), false)),
+        _ => (),
+    }
+
+    if spec == b"type" {
+        return match e {
+            Expr::LabelOrAttr(expr::LabelOrAttr::Attr(a)) => Ok(Expr::TypeOf(a)),
+            _ => Err(Error::Parse {
+                string: utf8(spec),
+                context: utf8(expr),
+                reason: "the type format spec can only be used with attributes",
+            }),
+        };
+    }
+    let (fill, width) = match spec {
+        [b'0', width @ ..] if !width.is_empty() => (b'0', width),
+        width => (b' ', width),
+    };
+    let width = std::str::from_utf8(width)
+        .ok()
+        .and_then(|w| w.parse::<usize>().ok())
+        .ok_or_else(|| Error::Parse {
+            string: utf8(spec),
+            context: utf8(expr),
+            reason: "invalid format spec. Expected a width, optionally prefixed with 0",
+        })?;
+
+    Ok(Expr::Pad(Box::new(e), width, fill))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fastq::Origin;
+    use std::sync::Arc;
+
+    fn read() -> Read {
+        Read::from_fastq1(b"read", b"AcGtN", b"aBcDe", Arc::new(Origin::Bytes), 0)
+    }
+
+    fn format(expr: &str, use_qual: bool) -> Vec<u8> {
+        FormatExpr::new(expr.as_bytes())
+            .unwrap()
+            .format(&read(), use_qual)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_case() {
+        assert_eq!(format("{seq1.*:upper}", false), b"ACGTN");
+        assert_eq!(format("{seq1.*:lower}", false), b"acgtn");
+        assert_eq!(format("xY_{seq1.*:upper}_Yx", false), b"xY_ACGTN_Yx");
+    }
+
+    #[test]
+    fn test_case_qual() {
+        assert_eq!(format("{seq1.*:upper}", true), b"aBcDe");
+        assert_eq!(format("{seq1.*:lower}", true), b"aBcDe");
+    }
+}
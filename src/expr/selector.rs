@@ -18,6 +18,36 @@ impl SelectorExpr {
     pub fn matches(&self, read: &Read) -> std::result::Result<bool, NameError> {
         matches_rec(&self.expr, read)
     }
+
+    /// Only select reads that also have all of the specified labels and attributes.
+    ///
+    /// This is used by operations to skip reads that do not have their inputs.
+    pub fn require(self, names: impl IntoIterator<Item = expr::LabelOrAttr>) -> Self {
+        let mut exprs = names.into_iter().map(Expr::Exists).collect::<Vec<_>>();
+
+        if exprs.is_empty() {
+            return self;
+        }
+
+        // check the required names first so the rest of the expression is skipped
+        exprs.push(self.expr);
+        Self {
+            expr: Expr::And(exprs),
+        }
+    }
+
+    /// Only select reads that also have a boolean attribute with the specified value.
+    pub(crate) fn with_attr(self, attr: expr::Attr, value: bool) -> Self {
+        let attr_expr = if value {
+            Expr::Attr(attr)
+        } else {
+            Expr::Not(Box::new(Expr::Attr(attr)))
+        };
+
+        Self {
+            expr: Expr::And(vec![self.expr, attr_expr]),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -28,9 +58,43 @@ enum Item {
     Or,
     Not,
     Dot,
+    Cmp(CmpOp),
     Label(Vec<u8>),
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl CmpOp {
+    fn cmp(&self, a: f64, b: f64) -> bool {
+        use CmpOp::*;
+        match self {
+            Lt => a < b,
+            Le => a <= b,
+            Gt => a > b,
+            Ge => a >= b,
+            Eq => a == b,
+            Ne => a != b,
+        }
+    }
+
+    /// Compare values that do not have an order, which only works with `==` and `!=`.
+    fn cmp_eq<T: PartialEq>(&self, a: T, b: T) -> bool {
+        match self {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            _ => unreachable!(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum Expr {
     True,
@@ -39,6 +103,9 @@ enum Expr {
     Not(Box<Expr>),
     Label(expr::Label),
     Attr(expr::Attr),
+    Exists(expr::LabelOrAttr),
+    /// The value is a float, or a bool or bytes when comparing with `==` or `!=`.
+    Cmp(expr::Attr, CmpOp, Data),
 }
 
 fn matches_rec(expr: &Expr, read: &Read) -> std::result::Result<bool, NameError> {
@@ -76,6 +143,26 @@ fn matches_rec(expr: &Expr, read: &Read) -> std::result::Result<bool, NameError>
             label,
             attr,
         }) => Ok(read.data(*str_type, *label, *attr)?.as_bool()),
+        Exists(l) => Ok(l.exists(read)),
+        Cmp(
+            expr::Attr {
+                str_type,
+                label,
+                attr,
+            },
+            op,
+            x,
+        ) => {
+            let data = read.data(*str_type, *label, *attr)?;
+            match (data, x) {
+                (_, Data::Float(x)) => Ok(op.cmp(data.as_float()?, *x)),
+                (Data::Bool(a), Data::Bool(b)) => Ok(op.cmp_eq(a, b)),
+                (_, Data::Bool(_)) => Err(NameError::Type("bool", data.clone())),
+                (Data::Bytes(a), Data::Bytes(b)) => Ok(op.cmp_eq(a, b)),
+                (_, Data::Bytes(_)) => Err(NameError::Type("bytes", data.clone())),
+                (_, Data::UInt(_)) => unreachable!(),
+            }
+        }
     }
 }
 
@@ -102,7 +189,9 @@ fn lex(expr_str: &[u8]) -> Result<Vec<Item>> {
         Ok(())
     };
 
-    for &c in expr_str {
+    let mut chars = expr_str.iter().cloned().peekable();
+
+    while let Some(c) = chars.next() {
         match c {
             b'(' => {
                 write_curr(&mut res, &mut curr, true)?;
@@ -120,14 +209,40 @@ fn lex(expr_str: &[u8]) -> Result<Vec<Item>> {
                 write_curr(&mut res, &mut curr, false)?;
                 res.push(Or);
             }
+            b'!' if chars.next_if_eq(&b'=').is_some() => {
+                write_curr(&mut res, &mut curr, false)?;
+                res.push(Cmp(CmpOp::Ne));
+            }
             b'!' => {
                 write_curr(&mut res, &mut curr, true)?;
                 res.push(Not);
             }
+            // numbers after a comparison can start with a sign or a decimal point
+            b'-' if curr.is_empty() && matches!(res.last(), Some(Cmp(_))) => curr.push(c),
+            b'.' if matches!(curr[..], [] | [b'-']) && matches!(res.last(), Some(Cmp(_))) => {
+                curr.push(c)
+            }
             b'.' => {
                 write_curr(&mut res, &mut curr, false)?;
                 res.push(Dot);
             }
+            b'<' | b'>' | b'=' => {
+                write_curr(&mut res, &mut curr, false)?;
+                let eq = chars.next_if_eq(&b'=').is_some();
+                let op = match (c, eq) {
+                    (b'<', false) => CmpOp::Lt,
+                    (b'<', true) => CmpOp::Le,
+                    (b'>', false) => CmpOp::Gt,
+                    (b'>', true) => CmpOp::Ge,
+                    (b'=', true) => CmpOp::Eq,
+                    _ => Err(Error::Parse {
+                        string: (c as char).to_string(),
+                        context: utf8(expr_str),
+                        reason: "invalid comparison",
+                    })?,
+                };
+                res.push(Cmp(op));
+            }
             b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' | b'*' => curr.push(c),
             _ if c.is_ascii_whitespace() => (),
             _ => Err(Error::Parse {
@@ -195,6 +310,8 @@ fn parse(items: &[Item]) -> Result<Expr> {
 
     if let Item::Not = items[0] {
         Ok(Expr::Not(Box::new(parse(&items[1..])?)))
+    } else if let Some(idx) = items.iter().position(|i| matches!(i, Item::Cmp(_))) {
+        parse_cmp(&items[..idx], &items[idx], &items[idx + 1..])
     } else {
         Err(Error::Parse {
             string: "".to_owned(),
@@ -204,6 +321,69 @@ fn parse(items: &[Item]) -> Result<Expr> {
     }
 }
 
+/// Parse a comparison between an attribute and a number, like `seq1.*.len >= 10`.
+///
+/// With `==` and `!=`, the attribute can also be compared with `true`, `false`, or bytes, like
+/// `seq1.*.sample != undetermined`.
+fn parse_cmp(lhs: &[Item], op: &Item, rhs: &[Item]) -> Result<Expr> {
+    use Item::{Dot, Label};
+
+    let Item::Cmp(op) = *op else { unreachable!() };
+
+    let attr = match lhs {
+        [Label(str_type), Dot, Label(label), Dot, Label(attr)] => expr::Attr {
+            str_type: StrType::new(str_type)?,
+            label: InlineString::new(label),
+            attr: InlineString::new(attr),
+        },
+        _ => Err(Error::Parse {
+            string: "".to_owned(),
+            context: "".to_owned(),
+            reason: "expected type.label.attr before the comparison",
+        })?,
+    };
+
+    // numbers with a decimal point are split into multiple items
+    let mut value = Vec::new();
+    for item in rhs {
+        match item {
+            Label(l) => value.extend_from_slice(l),
+            Dot => value.push(b'.'),
+            _ => Err(Error::Parse {
+                string: "".to_owned(),
+                context: "".to_owned(),
+                reason: "expected a number after the comparison",
+            })?,
+        }
+    }
+
+    let is_num = matches!(value.first(), Some(b'0'..=b'9' | b'-' | b'.'));
+
+    if let Some(num) = std::str::from_utf8(&value)
+        .ok()
+        .filter(|_| is_num)
+        .and_then(|n| n.parse::<f64>().ok())
+    {
+        return Ok(Expr::Cmp(attr, op, Data::Float(num)));
+    }
+
+    if !matches!(op, CmpOp::Eq | CmpOp::Ne) || value.is_empty() {
+        Err(Error::Parse {
+            string: utf8(&value),
+            context: "".to_owned(),
+            reason: "expected a number after the comparison",
+        })?;
+    }
+
+    let value = match &value[..] {
+        b"true" => Data::Bool(true),
+        b"false" => Data::Bool(false),
+        _ => Data::Bytes(value),
+    };
+
+    Ok(Expr::Cmp(attr, op, value))
+}
+
 fn split_skip_parens<F>(items: &[Item], delim: Item, mut f: F) -> Result<bool>
 where
     F: FnMut(&[Item]) -> Result<()>,
@@ -251,3 +431,76 @@ fn unwrap_parens(items: &[Item]) -> &[Item] {
     let c = c1.min(c2);
     &items[c..items.len() - c]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fastq::Origin;
+    use std::sync::Arc;
+
+    fn read_with(data: Data) -> Read {
+        let mut read = Read::from_fastq1(b"read", b"AAAA", b"IIII", Arc::new(Origin::Bytes), 0);
+        *read
+            .data_mut(
+                StrType::Seq1,
+                InlineString::new(b"*"),
+                InlineString::new(b"x"),
+            )
+            .unwrap() = data;
+        read
+    }
+
+    fn matches(expr: &str, data: Data) -> std::result::Result<bool, NameError> {
+        SelectorExpr::new(expr.as_bytes())
+            .unwrap()
+            .matches(&read_with(data))
+    }
+
+    #[test]
+    fn test_ne_uint() {
+        assert!(matches("seq1.*.x != 3", Data::UInt(2)).unwrap());
+        assert!(!matches("seq1.*.x != 3", Data::UInt(3)).unwrap());
+    }
+
+    #[test]
+    fn test_ne_float() {
+        assert!(matches("seq1.*.x != 0.5", Data::Float(-0.5)).unwrap());
+        assert!(!matches("seq1.*.x != -0.5", Data::Float(-0.5)).unwrap());
+    }
+
+    #[test]
+    fn test_ne_bool() {
+        assert!(matches("seq1.*.x != true", Data::Bool(false)).unwrap());
+        assert!(!matches("seq1.*.x != false", Data::Bool(false)).unwrap());
+        assert!(matches("seq1.*.x == true", Data::Bool(true)).unwrap());
+    }
+
+    #[test]
+    fn test_ne_bytes() {
+        let sample = || Data::Bytes(b"undetermined".to_vec());
+        assert!(!matches("seq1.*.x != undetermined", sample()).unwrap());
+        assert!(matches("seq1.*.x == undetermined", sample()).unwrap());
+        assert!(matches("seq1.*.x != sample_1", sample()).unwrap());
+    }
+
+    #[test]
+    fn test_cmp_type_mismatch() {
+        assert!(matches!(
+            matches("seq1.*.x != 3", Data::Bytes(b"3".to_vec())),
+            Err(NameError::Type(_, Data::Bytes(_)))
+        ));
+        assert!(matches!(
+            matches("seq1.*.x != true", Data::UInt(1)),
+            Err(NameError::Type("bool", Data::UInt(1)))
+        ));
+        assert!(matches!(
+            matches("seq1.*.x != undetermined", Data::Float(1.0)),
+            Err(NameError::Type("bytes", Data::Float(_)))
+        ));
+    }
+
+    #[test]
+    fn test_cmp_order_requires_number() {
+        assert!(SelectorExpr::new(b"seq1.*.x < undetermined").is_err());
+    }
+}
@@ -43,6 +43,15 @@ impl TransformExpr {
         );
     }
 
+    /// Input labels that must exist in a read.
+    pub fn required_names(&self) -> Vec<LabelOrAttr> {
+        self.before
+            .iter()
+            .cloned()
+            .map(LabelOrAttr::Label)
+            .collect()
+    }
+
     pub fn before(&self) -> &[Label] {
         &self.before
     }
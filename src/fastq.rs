@@ -1,11 +1,13 @@
 use needletail::*;
 
 use std::fmt;
-use std::io::Write;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 use crate::errors::*;
+use crate::expr::UNKNOWN_QUAL;
 use crate::iter::*;
 use crate::read::*;
 
@@ -15,6 +17,18 @@ pub struct Fastq1Reads<'a> {
     idx: AtomicUsize,
     chunk_size: usize,
     interleaved: bool,
+    max_read_len: Option<usize>,
+}
+
+impl<'a> Fastq1Reads<'a> {
+    /// Error when the sequence of a record is longer than `max_read_len`.
+    ///
+    /// This is checked before the record is turned into a `Read`, to guard against malformed
+    /// inputs with gigantic records.
+    pub fn with_max_read_len(mut self, max_read_len: usize) -> Self {
+        self.max_read_len = Some(max_read_len);
+        self
+    }
 }
 
 impl<'a> Reads for Fastq1Reads<'a> {
@@ -33,6 +47,12 @@ impl<'a> Reads for Fastq1Reads<'a> {
                     idx: self.idx.load(Ordering::Relaxed),
                     source: Box::new(e),
                 })?;
+                check_read_len(
+                    record1.seq().len(),
+                    self.max_read_len,
+                    &self.origin,
+                    self.idx.load(Ordering::Relaxed),
+                )?;
 
                 if self.interleaved {
                     record1_id.clear();
@@ -65,6 +85,12 @@ impl<'a> Reads for Fastq1Reads<'a> {
                     idx: self.idx.load(Ordering::Relaxed) + 1,
                     source: Box::new(e),
                 })?;
+                check_read_len(
+                    record2.seq().len(),
+                    self.max_read_len,
+                    &self.origin,
+                    self.idx.load(Ordering::Relaxed) + 1,
+                )?;
                 let idx = self.idx.fetch_add(2, Ordering::Relaxed);
 
                 res.push(Read::from_fastq2(
@@ -97,6 +123,18 @@ pub struct Fastq2Reads {
     origin2: Arc<Origin>,
     idx: AtomicUsize,
     chunk_size: usize,
+    max_read_len: Option<usize>,
+}
+
+impl Fastq2Reads {
+    /// Error when the sequence of a record in either file is longer than `max_read_len`.
+    ///
+    /// This is checked before the records are turned into a `Read`, to guard against malformed
+    /// inputs with gigantic records.
+    pub fn with_max_read_len(mut self, max_read_len: usize) -> Self {
+        self.max_read_len = Some(max_read_len);
+        self
+    }
 }
 
 impl Reads for Fastq2Reads {
@@ -124,6 +162,18 @@ impl Reads for Fastq2Reads {
                 idx: self.idx.load(Ordering::Relaxed),
                 source: Box::new(e),
             })?;
+            check_read_len(
+                record1.seq().len(),
+                self.max_read_len,
+                &self.origin1,
+                self.idx.load(Ordering::Relaxed),
+            )?;
+            check_read_len(
+                record2.seq().len(),
+                self.max_read_len,
+                &self.origin2,
+                self.idx.load(Ordering::Relaxed),
+            )?;
             let idx = self.idx.fetch_add(1, Ordering::Relaxed);
 
             res.push(Read::from_fastq2(
@@ -164,6 +214,7 @@ pub fn iter_fastq1(file: impl AsRef<str>, chunk_size: usize) -> Result<Fastq1Rea
         idx: AtomicUsize::new(0),
         chunk_size,
         interleaved: false,
+        max_read_len: None,
     })
 }
 
@@ -186,6 +237,7 @@ pub fn iter_fastq_interleaved(
         idx: AtomicUsize::new(0),
         chunk_size,
         interleaved: true,
+        max_read_len: None,
     })
 }
 
@@ -214,6 +266,7 @@ pub fn iter_fastq2(
         origin2: Arc::new(Origin::File(file2.as_ref().to_owned())),
         idx: AtomicUsize::new(0),
         chunk_size,
+        max_read_len: None,
     })
 }
 
@@ -227,6 +280,7 @@ pub fn iter_fastq1_bytes<'a>(bytes: &'a [u8]) -> Result<Fastq1Reads<'a>> {
         idx: AtomicUsize::new(0),
         chunk_size: 256,
         interleaved: false,
+        max_read_len: None,
     })
 }
 
@@ -240,6 +294,189 @@ pub fn iter_fastq_interleaved_bytes<'a>(bytes: &'a [u8]) -> Result<Fastq1Reads<'
         idx: AtomicUsize::new(0),
         chunk_size: 256,
         interleaved: true,
+        max_read_len: None,
+    })
+}
+
+fn check_read_len(
+    len: usize,
+    max_read_len: Option<usize>,
+    origin: &Origin,
+    idx: usize,
+) -> Result<()> {
+    match max_read_len {
+        Some(max_len) if len > max_len => Err(Error::ReadTooLong {
+            origin: origin.clone(),
+            idx,
+            len,
+            max_len,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// The type of a column in a delimited table of reads.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TableColumn {
+    /// A string of a certain type, like `name1` or `seq1`.
+    Str(StrType),
+    /// The quality scores of a string of a certain type.
+    Qual(StrType),
+    /// A column that is ignored.
+    Skip,
+}
+
+pub struct TableReads {
+    reader: Mutex<Box<dyn BufRead + Send>>,
+    origin: Arc<Origin>,
+    idx: AtomicUsize,
+    chunk_size: usize,
+    delimiter: u8,
+    columns: Vec<TableColumn>,
+}
+
+impl Reads for TableReads {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut res = Vec::with_capacity(self.chunk_size);
+        let mut line = Vec::new();
+
+        let mut reader = self.reader.lock().unwrap();
+
+        while res.len() < self.chunk_size {
+            line.clear();
+            let len = reader
+                .read_until(b'\n', &mut line)
+                .map_err(|e| Error::ParseRecord {
+                    origin: (*self.origin).clone(),
+                    idx: self.idx.load(Ordering::Relaxed),
+                    source: Box::new(e),
+                })?;
+
+            if len == 0 {
+                break;
+            }
+
+            while matches!(line.last(), Some(b'\n' | b'\r')) {
+                line.pop();
+            }
+
+            // skip empty lines
+            if line.is_empty() {
+                continue;
+            }
+
+            let idx = self.idx.fetch_add(1, Ordering::Relaxed);
+            res.push(self.parse_row(&line, idx)?);
+        }
+
+        Ok(res)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl TableReads {
+    fn parse_row(&self, line: &[u8], idx: usize) -> Result<Read> {
+        let fields = line.split(|&c| c == self.delimiter).collect::<Vec<_>>();
+
+        if fields.len() < self.columns.len() {
+            return Err(Error::Parse {
+                string: utf8(line),
+                context: self.origin.to_string(),
+                reason: "not enough columns in the table row",
+            });
+        }
+
+        let mut strs = Vec::new();
+
+        for (&column, &field) in self.columns.iter().zip(&fields) {
+            if let TableColumn::Str(str_type) = column {
+                strs.push((str_type, field.to_owned(), None));
+            }
+        }
+
+        for (&column, &field) in self.columns.iter().zip(&fields) {
+            if let TableColumn::Qual(str_type) = column {
+                let (_, string, qual) = strs.iter_mut().find(|(t, _, _)| *t == str_type).unwrap();
+
+                if string.len() != field.len() {
+                    return Err(Error::Parse {
+                        string: utf8(line),
+                        context: self.origin.to_string(),
+                        reason: "quality scores and string have different lengths in the table row",
+                    });
+                }
+
+                *qual = Some(field.to_owned());
+            }
+        }
+
+        // sequences without quality scores get a default quality score
+        for (str_type, string, qual) in strs.iter_mut() {
+            if qual.is_none() && !matches!(str_type, StrType::Name1 | StrType::Name2) {
+                *qual = Some(vec![UNKNOWN_QUAL; string.len()]);
+            }
+        }
+
+        Ok(Read::from_strs(strs, Arc::clone(&self.origin), idx))
+    }
+}
+
+/// Create a read iterator over the rows of a delimited table (like a TSV file) of reads.
+///
+/// Each row is one read, and `columns` specifies the type of each column. There must be
+/// `name1` and `seq1` columns. Sequences without a quality score column get a default quality
+/// score. Extra columns at the end of each row are ignored.
+///
+/// Larger `chunk_size` uses more memory, but reduces the overhead of allocations, multithreading,
+/// etc.
+#[must_use]
+pub fn iter_table(
+    file: impl AsRef<str>,
+    delimiter: u8,
+    columns: impl Into<Vec<TableColumn>>,
+    chunk_size: usize,
+) -> Result<TableReads> {
+    let columns = columns.into();
+    let has_str = |str_type| columns.contains(&TableColumn::Str(str_type));
+    assert!(
+        has_str(StrType::Name1) && has_str(StrType::Seq1),
+        "Expected name1 and seq1 columns in the table"
+    );
+
+    for (i, &column) in columns.iter().enumerate() {
+        match column {
+            TableColumn::Str(str_type) => assert!(
+                !columns[i + 1..].contains(&column),
+                "Duplicate {str_type} column in the table"
+            ),
+            TableColumn::Qual(str_type) => {
+                assert!(
+                    has_str(str_type),
+                    "Expected a {str_type} column for the quality scores in the table"
+                );
+                assert!(
+                    !columns[i + 1..].contains(&column),
+                    "Duplicate {str_type} quality score column in the table"
+                );
+            }
+            TableColumn::Skip => (),
+        }
+    }
+
+    let reader = File::open(file.as_ref()).map_err(|e| Error::FileIo {
+        file: file.as_ref().to_owned(),
+        source: Box::new(e),
+    })?;
+    Ok(TableReads {
+        reader: Mutex::new(Box::new(BufReader::new(reader))),
+        origin: Arc::new(Origin::File(file.as_ref().to_owned())),
+        idx: AtomicUsize::new(0),
+        chunk_size,
+        delimiter,
+        columns,
     })
 }
 
@@ -1,5 +1,7 @@
+use std::cell::RefCell;
 use std::marker::{Send, Sync};
 use std::ops::RangeBounds;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 
@@ -56,10 +58,187 @@ use fork_reads::*;
 pub mod time_reads;
 use time_reads::*;
 
+pub mod consensus_reads;
+use consensus_reads::*;
+
+pub mod correct_by_frequency_reads;
+use correct_by_frequency_reads::*;
+
+pub mod hash_reads;
+use hash_reads::*;
+
+pub mod window_track_reads;
+use window_track_reads::*;
+
+pub mod is_homopolymer_reads;
+use is_homopolymer_reads::*;
+
+pub mod match_anchors_reads;
+use match_anchors_reads::*;
+
+pub mod case_reads;
+use case_reads::*;
+
+pub mod shuffle_reads;
+use shuffle_reads::*;
+
+pub mod cardinality_reads;
+use cardinality_reads::*;
+
+pub mod base_composition_reads;
+use base_composition_reads::*;
+
+pub mod branch_reads;
+use branch_reads::*;
+
+pub mod reverse_qual_reads;
+use reverse_qual_reads::*;
+
+pub mod dedup_bloom_reads;
+use dedup_bloom_reads::*;
+
+pub mod mean_qual_reads;
+use mean_qual_reads::*;
+
+pub mod difference_reads;
+use difference_reads::*;
+
+pub mod gaps_reads;
+use gaps_reads::*;
+
+pub mod phred33_reads;
+use phred33_reads::*;
+
+pub mod attr_to_seq_reads;
+use attr_to_seq_reads::*;
+
+pub mod on_chunk_reads;
+use on_chunk_reads::*;
+
+pub mod strip_reads;
+use strip_reads::*;
+
+pub mod qual_split_reads;
+use qual_split_reads::*;
+
+pub mod merge_labels_reads;
+use merge_labels_reads::*;
+
+pub mod orientation_reads;
+use orientation_reads::*;
+
+pub mod trim_poly_tail_reads;
+use trim_poly_tail_reads::*;
+
+pub mod qual_weighted_gc_reads;
+use qual_weighted_gc_reads::*;
+
+pub mod split_index_reads;
+use split_index_reads::*;
+
+pub mod present_reads;
+use present_reads::*;
+
+pub mod mark_reads;
+use mark_reads::*;
+
+pub mod map_lookup_reads;
+use map_lookup_reads::*;
+
+pub mod demux_reads;
+use demux_reads::*;
+
+pub mod flat_map_reads;
+use flat_map_reads::*;
+
+pub mod coverage_reads;
+use coverage_reads::*;
+
+pub mod trim_read_through_reads;
+use trim_read_through_reads::*;
+
+pub mod first_low_qual_reads;
+use first_low_qual_reads::*;
+
+pub mod renumber_reads;
+use renumber_reads::*;
+
+pub mod cycle_reads;
+use cycle_reads::*;
+
+pub mod max_n_frac_reads;
+use max_n_frac_reads::*;
+
+pub mod crop_reads;
+use crop_reads::*;
+
+pub mod min_length_reads;
+use min_length_reads::*;
+
+pub mod entropy_reads;
+use entropy_reads::*;
+
+pub mod mate_guided_cut_reads;
+use mate_guided_cut_reads::*;
+
+pub mod length_summary_reads;
+use length_summary_reads::*;
+
+pub mod max_bases_reads;
+use max_bases_reads::*;
+
+pub mod split_on_reads;
+use split_on_reads::*;
+
+pub mod pair_by_name_reads;
+use pair_by_name_reads::*;
+
+pub mod trailing_high_qual_reads;
+use trailing_high_qual_reads::*;
+
+pub mod canonical_reads;
+use canonical_reads::*;
+
+pub mod attr_stats_reads;
+use attr_stats_reads::*;
+
+pub mod shard_reads;
+use shard_reads::*;
+
+pub mod recalibrate_qual_reads;
+use recalibrate_qual_reads::*;
+
+pub mod numeric_reads;
+use numeric_reads::*;
+
+pub mod replace_reads;
+use replace_reads::*;
+
+pub mod contains_reads;
+use contains_reads::*;
+
+pub mod gc_content_reads;
+use gc_content_reads::*;
+
+pub mod translate_reads;
+use translate_reads::*;
+
+pub mod string_dist_reads;
+use string_dist_reads::*;
+
+pub mod expected_errors_reads;
+use expected_errors_reads::*;
+
 /// Shared interface for all read iterators.
 ///
 /// Many operations allow a select expression to be specified as the first parameter.
 /// This ensures that the operation is only be applied on the selected reads.
+///
+/// Operations are also skipped for reads that do not have the input mappings or attributes
+/// of the operation, like the input mappings of a transform expression or the mappings and
+/// attributes used in a format expression. The exception is the operations that write reads
+/// out, like [`Reads::collect_fastq1`], which return an error for selected reads that are
+/// missing a mapping or attribute used in a file or header format expression.
 pub trait Reads: Send + Sync {
     /// Run a `Reads` iterator until there are no more reads left.
     fn run(mut self) -> Result<()>
@@ -70,6 +249,26 @@ pub trait Reads: Send + Sync {
         self.finish()
     }
 
+    /// Run a `Reads` iterator until there are no more reads left, without stopping on errors
+    /// caused by individual reads.
+    ///
+    /// Each error is printed to stderr along with the offending read, and the number of errors is
+    /// returned at the end. The offending read is passed on without the rest of the failed
+    /// operation applied to it, and the other reads in the same chunk are still processed. Other
+    /// errors, like errors when reading input files, still stop the iterator.
+    ///
+    /// Use [`Reads::run_lenient_with_threads`] to run in parallel. The [`run!`] and
+    /// [`run_with_threads!`] macros are never lenient.
+    fn run_lenient(self) -> Result<usize>
+    where
+        Self: Sized,
+    {
+        let errors = Arc::new(AtomicUsize::new(0));
+        let res = with_lenient_errors(&errors, || self.run());
+
+        res.map(|_| errors.load(Ordering::Relaxed))
+    }
+
     /// Run a `Reads` iterator in parallel with multithreading.
     fn run_with_threads(mut self, threads: usize)
     where
@@ -93,6 +292,39 @@ pub trait Reads: Send + Sync {
             .unwrap_or_else(|e| panic!("Error when running: {e}"));
     }
 
+    /// Run a `Reads` iterator in parallel with multithreading, without stopping on errors
+    /// caused by individual reads.
+    ///
+    /// Errors are handled the same way as [`Reads::run_lenient`], and the number of errors
+    /// across all threads is returned at the end.
+    fn run_lenient_with_threads(mut self, threads: usize) -> usize
+    where
+        Self: Sized,
+    {
+        assert!(threads >= 1, "Number of threads must be greater than zero");
+
+        let errors = Arc::new(AtomicUsize::new(0));
+
+        thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| {
+                    with_lenient_errors(&errors, || {
+                        while !self
+                            .next_chunk()
+                            .unwrap_or_else(|e| panic!("Error when running: {e}"))
+                            .is_empty()
+                        {}
+                    })
+                });
+            }
+        });
+
+        with_lenient_errors(&errors, || self.finish())
+            .unwrap_or_else(|e| panic!("Error when running: {e}"));
+
+        errors.load(Ordering::Relaxed)
+    }
+
     /// Run a `Reads` iterator and collect the resulting `Read`s into a `Vec`.
     fn run_collect_reads(mut self) -> Result<Vec<Read>>
     where
@@ -124,6 +356,56 @@ pub trait Reads: Send + Sync {
         ForEachReads::new(self, selector_expr, func)
     }
 
+    /// Replace each selected read with zero or more reads produced by an arbitrary function.
+    ///
+    /// This is useful for custom logic that splits or drops reads, like splitting chimeric reads.
+    /// The produced reads are passed to later operations in the same chunk, in order. Since
+    /// chunks are processed in parallel with multithreading, the function may be called from
+    /// different threads at the same time.
+    #[must_use]
+    fn flat_map<F>(self, selector_expr: SelectorExpr, func: F) -> FlatMapReads<Self, F>
+    where
+        F: Fn(Read) -> Vec<Read> + Send + Sync,
+        Self: Sized,
+    {
+        FlatMapReads::new(self, selector_expr, func)
+    }
+
+    /// Split a read into multiple reads at all occurrences of a delimiter in a mapping.
+    ///
+    /// Each segment between non-overlapping occurrences of the delimiter becomes a separate
+    /// read, where the mapping is trimmed to only the segment. The delimiters are removed.
+    /// Leading and trailing segments before the first and after the last delimiter are kept,
+    /// but empty segments are dropped, so a read that is only delimiters is dropped. Reads
+    /// without the delimiter are kept as a single segment. The match type must be
+    /// [`MatchType::ExactSearch`] or [`MatchType::HammingSearch`].
+    ///
+    /// This is useful for concatemer data where sub-reads are joined by a known linker.
+    ///
+    /// The transform expression must have one input mapping and one output attribute, which is
+    /// set to the index of each segment.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.segment)`.
+    #[must_use]
+    fn split_on(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        delimiter: impl AsRef<[u8]>,
+        match_type: MatchType,
+    ) -> SplitOnReads<Self>
+    where
+        Self: Sized,
+    {
+        SplitOnReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            delimiter.as_ref().to_owned(),
+            match_type,
+        )
+    }
+
     /// Print each read to standard error.
     #[must_use]
     fn dbg(self, selector_expr: SelectorExpr) -> ForEachReads<Self, fn(&mut Read)>
@@ -144,211 +426,1419 @@ pub trait Reads: Send + Sync {
         CountReads::new(self, selector_exprs.into(), func)
     }
 
-    /// Check whether a mapping length is within the specified bounds.
+    /// Estimate the number of distinct values of a format expression across the selected reads
+    /// and apply an arbitrary function on the estimate at the end.
     ///
-    /// The transform expression must have one input mapping and one output mapping.
+    /// This uses a HyperLogLog sketch, so it uses a small, fixed amount of memory, but the
+    /// estimate is approximate (the standard error is around 1%).
     ///
-    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.in_bounds)`.
-    /// This will set `seq1.*.in_bounds` to a boolean indicating whether the length of `seq1.*`
-    /// is in the specified bounds.
+    /// Example `format_expr`: `"{seq1.barcode}{seq1.umi}"`.
     #[must_use]
-    fn length_in_bounds<B>(
+    fn cardinality<F>(
         self,
         selector_expr: SelectorExpr,
-        transform_expr: TransformExpr,
-        bounds: B,
-    ) -> LengthInBoundsReads<Self, B>
+        format_expr: impl AsRef<str>,
+        func: F,
+    ) -> CardinalityReads<Self, F>
     where
-        B: RangeBounds<usize> + Send + Sync,
+        F: Fn(f64) + Send + Sync,
         Self: Sized,
     {
-        LengthInBoundsReads::new(self, selector_expr, transform_expr, bounds)
+        CardinalityReads::new(
+            self,
+            selector_expr,
+            FormatExpr::new(format_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the cardinality operation: {e}")
+            }),
+            func,
+        )
     }
 
-    /// Set an attribute to true with some probability.
+    /// Summarize the lengths of a mapping across the selected reads and apply an arbitrary
+    /// function on the [`LengthSummary`] at the end.
     ///
-    /// This is deterministic, even with multithreading.
+    /// The summary includes the count, total bases, min, max, mean, median, and N50 of the
+    /// lengths, which is a standard summary for long read datasets. It can be printed with
+    /// `|s| eprintln!("{s}")`. Memory use is bounded by the number of distinct lengths, since
+    /// only a histogram of lengths is stored.
     #[must_use]
-    fn bernoulli(
+    fn length_summary<F>(
         self,
         selector_expr: SelectorExpr,
-        attr: Attr,
-        prob: f64,
-        seed: u32,
-    ) -> BernoulliReads<Self>
+        label: Label,
+        func: F,
+    ) -> LengthSummaryReads<Self, F>
     where
+        F: Fn(&LengthSummary) + Send + Sync,
         Self: Sized,
     {
-        BernoulliReads::new(self, selector_expr, attr, prob, seed)
+        LengthSummaryReads::new(self, selector_expr, label, func)
     }
 
-    /// Cut a mapping at an index to create two new mappings.
-    ///
-    /// The transform expression must have one input mapping and two output mappings.
+    /// Summarize the values of an attribute across the selected reads and apply an arbitrary
+    /// function on the [`AttrStats`] at the end.
     ///
-    /// Example `transform_expr`: `tr!(seq1.* -> seq1.left, seq1.right)`.
+    /// Unsigned integer and float values are summarized by their min, max, mean, and quartiles.
+    /// Bytes and boolean values are counted, and the `top_n` most common values are kept. It can
+    /// be printed with `|s| eprint!("{s}")`. Memory use is bounded by the number of distinct
+    /// values, since only a count of each distinct value is stored, so attributes with many
+    /// distinct values, like unrounded floats, use memory proportional to the number of reads.
     #[must_use]
-    fn cut(
+    fn attr_stats<F>(
         self,
         selector_expr: SelectorExpr,
-        transform_expr: TransformExpr,
-        cut_idx: EndIdx,
-    ) -> CutReads<Self>
+        attr: Attr,
+        top_n: usize,
+        func: F,
+    ) -> AttrStatsReads<Self, F>
     where
+        F: Fn(&AttrStats) + Send + Sync,
         Self: Sized,
     {
-        CutReads::new(self, selector_expr, transform_expr, cut_idx)
+        AttrStatsReads::new(self, selector_expr, attr, top_n, func)
     }
 
-    /// Intersect two mapping intervals and create a new mapping of the intersection, if it is not empty.
-    ///
-    /// The transform expression must have two input mappings and one output mapping.
+    /// Compute the per-position base composition of a mapping across the selected reads and write
+    /// it to a TSV file at the end.
     ///
-    /// Example `transform_expr`: `tr!(seq1.a, seq1.b -> seq1.c)`.
+    /// Each row of the TSV file contains the fraction of A, C, G, T, and other bases (N) at one
+    /// position. Only the first `max_len` positions are counted, and reads that are shorter than
+    /// a position do not contribute to that position.
     #[must_use]
-    fn intersect(
+    fn base_composition(
         self,
         selector_expr: SelectorExpr,
-        transform_expr: TransformExpr,
-    ) -> IntersectReads<Self>
+        label: Label,
+        max_len: usize,
+        file: impl AsRef<str>,
+    ) -> BaseCompositionReads<Self>
     where
         Self: Sized,
     {
-        IntersectReads::new(self, selector_expr, transform_expr)
+        BaseCompositionReads::new(
+            self,
+            selector_expr,
+            label,
+            max_len,
+            file.as_ref().to_owned(),
+        )
     }
 
-    /// Union two mapping intervals and create a new mapping of the union.
-    ///
-    /// If the two mapping intervals are disjoint, then the union will also contain the region
-    /// between the two mapping intervals, which is not inside either mapping intervals.
+    /// Check whether a mapping length is within the specified bounds.
     ///
-    /// The transform expression must have two input mappings and one output mapping.
+    /// The transform expression must have one input mapping and one output mapping.
     ///
-    /// Example `transform_expr`: `tr!(seq1.a, seq1.b -> seq1.c)`.
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.in_bounds)`.
+    /// This will set `seq1.*.in_bounds` to a boolean indicating whether the length of `seq1.*`
+    /// is in the specified bounds.
     #[must_use]
-    fn union(self, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> UnionReads<Self>
+    fn length_in_bounds<B>(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        bounds: B,
+    ) -> LengthInBoundsReads<Self, B>
     where
+        B: RangeBounds<usize> + Send + Sync,
         Self: Sized,
     {
-        UnionReads::new(self, selector_expr, transform_expr)
+        LengthInBoundsReads::new(self, selector_expr, transform_expr, bounds)
     }
 
-    /// Trim the mappings corresponding to the specified labels by modifying the underlying strings.
+    /// Apply a numeric operation on an unsigned integer or float attribute, and store the result
+    /// into a new attribute.
     ///
-    /// When a mapping is trimmed, its length will be set to zero. All intersecting
-    /// mappings will also be adjusted accordingly for the shortening.
+    /// The other operands of the operation can be attributes or constants. Reads that do not
+    /// have the input or operand attributes are skipped. Operands with mismatched types or
+    /// division by zero cause an error.
+    ///
+    /// Example `op`: `NumericOp::Rem(4.into())`.
     #[must_use]
-    fn trim(self, selector_expr: SelectorExpr, labels: impl Into<Vec<Label>>) -> TrimReads<Self>
+    fn numeric(
+        self,
+        selector_expr: SelectorExpr,
+        attr: Attr,
+        new_attr: Attr,
+        op: NumericOp,
+    ) -> NumericReads<Self>
     where
         Self: Sized,
     {
-        TrimReads::new(self, selector_expr, labels.into())
+        NumericReads::new(self, selector_expr, attr, new_attr, op)
     }
 
-    /// Set a label or attribute to the result of a format expression.
+    /// Check whether a mapping contains a substring, and store the boolean result into an
+    /// attribute.
     ///
-    /// After a label is set, its mapping and all other intersecting mappings will be adjusted accordingly
-    /// for any shortening or lengthening.
+    /// This is a cheap alternative to pattern matching for filtering by an exact substring, by
+    /// using the attribute in a selector expression afterwards. An empty substring is always
+    /// contained.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.has_linker)`.
     #[must_use]
-    fn set(
+    fn contains(
         self,
         selector_expr: SelectorExpr,
-        label_or_attr: impl Into<LabelOrAttr>,
-        format_expr: impl AsRef<str>,
-    ) -> SetReads<Self>
+        transform_expr: TransformExpr,
+        needle: impl AsRef<[u8]>,
+    ) -> ContainsReads<Self>
     where
         Self: Sized,
     {
-        SetReads::new(
+        ContainsReads::new(
             self,
             selector_expr,
-            label_or_attr.into(),
-            FormatExpr::new(format_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
-                panic!("Error in parsing format expression for the set operation: {e}")
-            }),
+            transform_expr,
+            needle.as_ref().to_owned(),
+            SubstringKind::Contains,
         )
     }
 
-    /// Match a regex pattern in a mapping.
+    /// Check whether a mapping starts with a prefix, and store the boolean result into an
+    /// attribute.
     ///
-    /// If named capture groups are used, then mappings are automatically created at the match
-    /// locations, labeled by the names specified in the regex.
+    /// An empty prefix always matches.
     ///
     /// The transform expression must have one input mapping and one output attribute.
     ///
-    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.matched)`.
-    /// This will match the regex pattern two `seq1.*` and set `seq1.*.matched` to a boolean
-    /// indicating whether the regex matches.
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.starts_with_primer)`.
     #[must_use]
-    fn match_regex(
+    fn starts_with(
         self,
         selector_expr: SelectorExpr,
         transform_expr: TransformExpr,
-        regex: impl AsRef<str>,
-    ) -> MatchRegexReads<Self>
+        prefix: impl AsRef<[u8]>,
+    ) -> ContainsReads<Self>
     where
         Self: Sized,
     {
-        MatchRegexReads::new(self, selector_expr, transform_expr, regex.as_ref())
+        ContainsReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            prefix.as_ref().to_owned(),
+            SubstringKind::StartsWith,
+        )
     }
 
-    /// Match any one of multiple patterns in a mapping.
-    ///
-    /// Patterns are specified in YAML format:
-    /// ```
-    /// name: my_patterns
-    /// patterns:
-    ///   - pattern: AAAA
-    ///     some_extra_data1: !String "all As"
-    ///     some_extra_data2: !Bool true
-    ///   - pattern: TTTT
-    ///     some_extra_data1: !String "all Ts"
-    ///     some_extra_data2: !Bool false
-    /// ```
-    ///
-    /// Patterns can be arbitrary format expressions, so you can use any existing mappings or
-    /// attributes as patterns.
+    /// Check whether a mapping ends with a suffix, and store the boolean result into an
+    /// attribute.
     ///
-    /// You can also include arbitrary extra attributes, like `some_extra_data1` and
-    /// `some_extra_data2` in this example. The corresponding attributes for the matched pattern
-    /// will be stored into the input mapping.
+    /// An empty suffix always matches.
     ///
-    /// The transform expression must have one input mapping and the number of output mappings is
-    /// determined by the [`MatchType`].
+    /// The transform expression must have one input mapping and one output attribute.
     ///
-    /// Example `transform_expr` for local-alignment-based pattern matching:
-    /// `tr!(seq1.* -> seq1.before, seq1.aligned, seq1.after)`.
-    /// The input mapping will get a new attribute (`seq1.*.my_patterns`) that is set to the pattern
-    /// that is matched. If no pattern matches, then it will be set to false.
-    /// Assuming pattern `AAAA` is matched, `seq1.*.some_extra_data1` will be set to `"all As"` and
-    /// `seq1.*.some_extra_data2` will be set to `true`.
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.ends_with_adapter)`.
     #[must_use]
-    fn match_any(
+    fn ends_with(
         self,
         selector_expr: SelectorExpr,
         transform_expr: TransformExpr,
-        patterns_yaml: impl AsRef<str>,
-        match_type: MatchType,
-    ) -> MatchAnyReads<Self>
+        suffix: impl AsRef<[u8]>,
+    ) -> ContainsReads<Self>
     where
         Self: Sized,
     {
-        MatchAnyReads::new(
+        ContainsReads::new(
             self,
             selector_expr,
             transform_expr,
-            Patterns::from_yaml(patterns_yaml.as_ref().as_bytes())
-                .unwrap_or_else(|e| panic!("Error in parsing patterns: {e}")),
-            match_type,
+            suffix.as_ref().to_owned(),
+            SubstringKind::EndsWith,
         )
     }
 
-    /// Match a pattern in a mapping.
+    /// Compute the Hamming distance (number of mismatches) between a mapping and another string,
+    /// and store it into an unsigned integer attribute.
     ///
-    /// The pattern can be an arbitrary format expression, so you can use any existing mappings or
-    /// attributes as patterns.
+    /// The other string is a format expression, so it can be a literal, like an expected
+    /// barcode, or another mapping. Strings with different lengths cause an error.
     ///
-    /// The transform expression must have one input mapping and the number of output mappings is
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.barcode -> seq1.barcode.dist)`.
+    ///
+    /// Example `other_expr`: `"ACGTACGT"` or `"{seq2.barcode}"`.
+    #[must_use]
+    fn hamming_dist(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        other_expr: impl AsRef<str>,
+    ) -> StringDistReads<Self>
+    where
+        Self: Sized,
+    {
+        StringDistReads::new_hamming(
+            self,
+            selector_expr,
+            transform_expr,
+            FormatExpr::new(other_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the hamming_dist operation: {e}")
+            }),
+        )
+    }
+
+    /// Compute the edit (Levenshtein) distance between a mapping and another string, and store it
+    /// into an unsigned integer attribute.
+    ///
+    /// Substitutions, insertions, and deletions all have a cost of one. The other string is a
+    /// format expression, so it can be a literal or another mapping. This is useful for fuzzy
+    /// comparisons of UMIs or barcodes that may have indels.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.umi -> seq1.umi.dist)`.
+    ///
+    /// Example `other_expr`: `"{seq2.umi}"`.
+    #[must_use]
+    fn edit_dist(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        other_expr: impl AsRef<str>,
+    ) -> StringDistReads<Self>
+    where
+        Self: Sized,
+    {
+        StringDistReads::new_edit(
+            self,
+            selector_expr,
+            transform_expr,
+            FormatExpr::new(other_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the edit_dist operation: {e}")
+            }),
+        )
+    }
+
+    /// Compute a 64-bit hash of a mapping.
+    ///
+    /// This is useful as a cheap, fixed-width key for deduplication or integrity checks.
+    /// The hash is non-cryptographic and it is not collision-free.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.hash)`.
+    /// This will set `seq1.*.hash` to the hash of `seq1.*` as an unsigned integer.
+    #[must_use]
+    fn hash(self, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> HashReads<Self>
+    where
+        Self: Sized,
+    {
+        HashReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Replace the names of reads with a prefix followed by a sequential number.
+    ///
+    /// Both mates of a paired-end read get the same name. The numbers start at zero and they
+    /// are unique across threads, but with multithreading the order of the numbers may not match
+    /// the order of the reads. This is useful for anonymizing names or building small test
+    /// datasets with compact names.
+    #[must_use]
+    fn renumber(self, selector_expr: SelectorExpr, prefix: impl AsRef<[u8]>) -> RenumberReads<Self>
+    where
+        Self: Sized,
+    {
+        RenumberReads::new(self, selector_expr, prefix.as_ref().to_owned())
+    }
+
+    /// Store the position of each read in a repeating cycle of `n` reads into an unsigned
+    /// integer attribute.
+    ///
+    /// The position is the index of the read in its input modulo `n`, so reads from instruments
+    /// that interleave `n` samples in a fixed round-robin order can be routed by position, like
+    /// with [`Reads::branch`]. For interleaved paired-end input, both records of a read are
+    /// counted once, so the two mates are never split across cycle positions.
+    #[must_use]
+    fn cycle(self, selector_expr: SelectorExpr, attr: Attr, n: usize) -> CycleReads<Self>
+    where
+        Self: Sized,
+    {
+        CycleReads::new(self, selector_expr, attr, n)
+    }
+
+    /// Compute per-window GC content and mean quality score tracks of a mapping.
+    ///
+    /// The mapping is split into consecutive windows of size `window` (the last window may be
+    /// shorter). The tracks are stored as JSON arrays with one value per window, which is
+    /// convenient for plotting. GC content is a fraction between 0 and 1 and quality scores
+    /// are Phred scores.
+    ///
+    /// The transform expression must have one input mapping and two output attributes.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.gc_track, seq1.*.qual_track)`.
+    /// Either output attribute can be `_` to skip computing that track.
+    #[must_use]
+    fn window_track(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        window: usize,
+    ) -> WindowTrackReads<Self>
+    where
+        Self: Sized,
+    {
+        WindowTrackReads::new(self, selector_expr, transform_expr, window)
+    }
+
+    /// Check whether a mapping consists of only one repeated base.
+    ///
+    /// The comparison is case-insensitive. Empty mappings are not homopolymers.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.is_homopolymer)`.
+    /// This will set `seq1.*.is_homopolymer` to a boolean indicating whether all bases in
+    /// `seq1.*` are the same.
+    #[must_use]
+    fn homopolymer(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> IsHomopolymerReads<Self>
+    where
+        Self: Sized,
+    {
+        IsHomopolymerReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Detect the relative orientation of two mates from how they overlap, and store it into an
+    /// attribute.
+    ///
+    /// The orientation is `FR` if the end of the first mapping overlaps the reverse complement
+    /// of the second mapping, `RF` if the reverse complement of the second mapping overlaps the
+    /// start of the first mapping, and `FF` if the end of the first mapping overlaps the second
+    /// mapping directly. The longest overlap with at least `min_overlap` bases and the `identity`
+    /// fraction of matching bases is picked. If there is no such overlap, then the attribute is
+    /// set to false.
+    ///
+    /// The reads are not modified, so this is useful for checking library preparation.
+    ///
+    /// The transform expression must have two input mappings and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.*, seq2.* -> seq1.*.orientation)`.
+    #[must_use]
+    fn orientation(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        min_overlap: usize,
+        identity: f64,
+    ) -> OrientationReads<Self>
+    where
+        Self: Sized,
+    {
+        OrientationReads::new(self, selector_expr, transform_expr, min_overlap, identity)
+    }
+
+    /// Trim read-through into the adapters on both mates of a paired-end read.
+    ///
+    /// When the insert is shorter than the read length, the end of the first mate reads into
+    /// `adapter1` and the end of the second mate reads into `adapter2`. Each adapter is first
+    /// searched for exactly, and then a partial adapter at the end of the mate is found with
+    /// suffix alignment (like [`MatchType::SuffixAln`]) using the `identity` and `overlap`
+    /// thresholds. Both mates are then cut at the shortest insert length found, so the
+    /// remaining inserts have the same length.
+    ///
+    /// Reads without read-through are not trimmed: the insert mappings will span each entire
+    /// mate and the adapter mappings will be empty.
+    ///
+    /// The transform expression must have two input mappings and four output mappings: the
+    /// insert and adapter of the first mate, then the insert and adapter of the second mate.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.*, seq2.* -> seq1.insert, seq1.adapter, seq2.insert, seq2.adapter)`.
+    #[must_use]
+    fn trim_read_through(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        adapter1: impl AsRef<[u8]>,
+        adapter2: impl AsRef<[u8]>,
+        identity: f64,
+        overlap: f64,
+    ) -> TrimReadThroughReads<Self>
+    where
+        Self: Sized,
+    {
+        TrimReadThroughReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            adapter1.as_ref().to_owned(),
+            adapter2.as_ref().to_owned(),
+            identity,
+            overlap,
+        )
+    }
+
+    /// Compute the Shannon entropy (in bits) of the frequencies of the k-mers in a mapping, and
+    /// store it into a float attribute.
+    ///
+    /// With `k = 1`, this is the entropy of the bases. Larger `k` captures repetitiveness, like
+    /// dinucleotide repeats. Low entropy indicates low complexity sequences. Mappings shorter
+    /// than `k` have zero entropy.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.entropy)`.
+    #[must_use]
+    fn entropy(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        k: usize,
+    ) -> EntropyReads<Self>
+    where
+        Self: Sized,
+    {
+        EntropyReads::new(self, selector_expr, transform_expr, k)
+    }
+
+    /// Compute the fraction of bases in a mapping that are G or C, and store it into a float
+    /// attribute.
+    ///
+    /// Lowercase bases are also counted, unless they are skipped as soft-masked bases with
+    /// [`GcContentReads::with_soft_mask`]. The GC content is zero for empty mappings. Reads can be
+    /// filtered by GC content with a comparison in a selector expression afterwards, like
+    /// `sel!(seq1.*.gc >= 0.4 & seq1.*.gc <= 0.6)`.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.gc)`.
+    #[must_use]
+    fn gc_content(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> GcContentReads<Self>
+    where
+        Self: Sized,
+    {
+        GcContentReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Translate a mapping in-frame to single-letter amino acids using the standard codon table,
+    /// and store the result into a bytes attribute.
+    ///
+    /// Translation stops after the first stop codon, which is translated to `*`. Codons with
+    /// ambiguous bases, like `N`, are translated to `X`, and a trailing partial codon is ignored.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.tag -> seq1.tag.protein)`.
+    #[must_use]
+    fn translate(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> TranslateReads<Self>
+    where
+        Self: Sized,
+    {
+        TranslateReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Compute the GC content of a mapping, where each base is weighted by the probability that
+    /// it is correct according to its quality score, and store it into a float attribute.
+    ///
+    /// The mapping must have quality scores. The GC content is zero for empty mappings.
+    /// Lowercase (soft-masked) bases can be skipped with
+    /// [`QualWeightedGcReads::with_soft_mask`].
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.gc)`.
+    #[must_use]
+    fn qual_weighted_gc(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> QualWeightedGcReads<Self>
+    where
+        Self: Sized,
+    {
+        QualWeightedGcReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Compute the mean Phred quality score of a mapping, rounded to the nearest integer.
+    ///
+    /// Reads without quality scores for the mapping are skipped. The mean of an empty mapping
+    /// is zero. The unrounded mean can be stored as a float with [`MeanQualReads::with_float`],
+    /// and reads without quality scores can cause an error with
+    /// [`MeanQualReads::with_require_qual`].
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.mean_qual)`.
+    /// This will set `seq1.*.mean_qual` to the mean quality score of `seq1.*`, which can then be
+    /// used in format expressions, like `"{name1.*}_{seq1.*.mean_qual}"`.
+    #[must_use]
+    fn mean_qual(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> MeanQualReads<Self>
+    where
+        Self: Sized,
+    {
+        MeanQualReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Compute the expected number of errors in a mapping from its quality scores (Phred+33),
+    /// and store it into a float attribute.
+    ///
+    /// This is the sum of the error probabilities `10^(-Q/10)` of each base, and it is zero for
+    /// empty mappings. Reads can be filtered by the expected number of errors with a comparison
+    /// in a selector expression afterwards, like `sel!(seq1.*.expected_errors < 1.0)`. Mappings
+    /// without quality scores cause an error.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.expected_errors)`.
+    #[must_use]
+    fn expected_errors(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> ExpectedErrorsReads<Self>
+    where
+        Self: Sized,
+    {
+        ExpectedErrorsReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Check whether all of the specified labels and attributes exist in a read, and store the
+    /// boolean result into an attribute.
+    ///
+    /// Missing labels and attributes never cause errors. This is useful for validating the
+    /// structure of reads.
+    #[must_use]
+    fn all_present(
+        self,
+        selector_expr: SelectorExpr,
+        names: impl Into<Vec<LabelOrAttr>>,
+        attr: Attr,
+    ) -> PresentReads<Self>
+    where
+        Self: Sized,
+    {
+        PresentReads::new(self, selector_expr, names.into(), attr, PresentKind::All)
+    }
+
+    /// Check whether any of the specified labels and attributes exist in a read, and store the
+    /// boolean result into an attribute.
+    ///
+    /// Missing labels and attributes never cause errors.
+    #[must_use]
+    fn any_present(
+        self,
+        selector_expr: SelectorExpr,
+        names: impl Into<Vec<LabelOrAttr>>,
+        attr: Attr,
+    ) -> PresentReads<Self>
+    where
+        Self: Sized,
+    {
+        PresentReads::new(self, selector_expr, names.into(), attr, PresentKind::Any)
+    }
+
+    /// Count how many of the specified labels and attributes exist in a read, and store the count
+    /// into an attribute.
+    ///
+    /// Missing labels and attributes are counted as absent instead of causing errors. This is
+    /// useful for keeping reads that matched at least some number of patterns, by using a
+    /// comparison in a selector expression afterwards, like `sel!(seq1.*.num_matched >= 2)`.
+    #[must_use]
+    fn num_present(
+        self,
+        selector_expr: SelectorExpr,
+        names: impl Into<Vec<LabelOrAttr>>,
+        attr: Attr,
+    ) -> PresentReads<Self>
+    where
+        Self: Sized,
+    {
+        PresentReads::new(self, selector_expr, names.into(), attr, PresentKind::Num)
+    }
+
+    /// Set an attribute to true with some probability.
+    ///
+    /// This is deterministic, even with multithreading.
+    #[must_use]
+    fn bernoulli(
+        self,
+        selector_expr: SelectorExpr,
+        attr: Attr,
+        prob: f64,
+        seed: u32,
+    ) -> BernoulliReads<Self>
+    where
+        Self: Sized,
+    {
+        BernoulliReads::new(self, selector_expr, attr, prob, seed)
+    }
+
+    /// Cut a mapping at an index to create two new mappings.
+    ///
+    /// If the index is beyond the length of the mapping, then it is clamped to the bounds of the
+    /// mapping by default. This can be changed with [`CutReads::with_out_of_range`].
+    ///
+    /// The cut index can be an [`EndIdx`] or an `isize`, where negative indices count from the
+    /// right end of the mapping. For example, `-4` cuts off the last 4 characters.
+    ///
+    /// The transform expression must have one input mapping and two output mappings.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.left, seq1.right)`.
+    #[must_use]
+    fn cut(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        cut_idx: impl Into<EndIdx>,
+    ) -> CutReads<Self>
+    where
+        Self: Sized,
+    {
+        CutReads::new(self, selector_expr, transform_expr, cut_idx.into())
+    }
+
+    /// Cut a mapping at a position that comes from the other mate of a paired-end read.
+    ///
+    /// The cut index is the length of the `source` mapping or the value of the `source`
+    /// unsigned integer attribute, which is usually set from the other mate. For example, after
+    /// matching the adapter in read 1 to create `seq1.insert`, read 2 can be cut to the same
+    /// insert length with `mate_guided_cut(sel!(), tr!(seq2.* -> seq2.insert, seq2.adapter), label!(seq1.insert))`.
+    /// Indices beyond the length of the mapping are clamped, like [`Reads::cut`].
+    ///
+    /// Reads without the `source` mapping or attribute are skipped.
+    ///
+    /// The transform expression must have one input mapping and two output mappings.
+    ///
+    /// Example `transform_expr`: `tr!(seq2.* -> seq2.insert, seq2.adapter)`.
+    #[must_use]
+    fn mate_guided_cut(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        source: impl Into<LabelOrAttr>,
+    ) -> MateGuidedCutReads<Self>
+    where
+        Self: Sized,
+    {
+        MateGuidedCutReads::new(self, selector_expr, transform_expr, source.into())
+    }
+
+    /// Split a combined dual-index mapping into i7 and i5 sub-barcodes with fixed lengths.
+    ///
+    /// The first `i7_len` bases become the first new mapping and the next `i5_len` bases become
+    /// the second new mapping. Any remaining bases are ignored. Reads where the mapping is
+    /// shorter than `i7_len + i5_len` will result in an error.
+    ///
+    /// To demultiplex on the combination of both sub-barcodes, they can be used in the file
+    /// name of [`Reads::collect_fastq1`], like `"{index1.i7}_{index1.i5}.fastq"`.
+    ///
+    /// The transform expression must have one input mapping and two output mappings.
+    ///
+    /// Example `transform_expr`: `tr!(index1.* -> index1.i7, index1.i5)`.
+    #[must_use]
+    fn split_index(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        i7_len: usize,
+        i5_len: usize,
+    ) -> SplitIndexReads<Self>
+    where
+        Self: Sized,
+    {
+        SplitIndexReads::new(self, selector_expr, transform_expr, i7_len, i5_len)
+    }
+
+    /// Intersect two mapping intervals and create a new mapping of the intersection, if it is not empty.
+    ///
+    /// The transform expression must have two input mappings and one output mapping.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.a, seq1.b -> seq1.c)`.
+    #[must_use]
+    fn intersect(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> IntersectReads<Self>
+    where
+        Self: Sized,
+    {
+        IntersectReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Union two mapping intervals and create a new mapping of the union.
+    ///
+    /// If the two mapping intervals are disjoint, then the union will also contain the region
+    /// between the two mapping intervals, which is not inside either mapping intervals.
+    ///
+    /// The transform expression must have two input mappings and one output mapping.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.a, seq1.b -> seq1.c)`.
+    #[must_use]
+    fn union(self, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> UnionReads<Self>
+    where
+        Self: Sized,
+    {
+        UnionReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Subtract one mapping interval from another and create two new mappings: the part of the
+    /// first mapping before the second mapping and the part of the first mapping after the second
+    /// mapping.
+    ///
+    /// Either new mapping can be empty. If the two mapping intervals are disjoint, then one new
+    /// mapping will be the entire first mapping and the other will be empty.
+    ///
+    /// The transform expression must have two input mappings and two output mappings.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.a, seq1.b -> seq1.left, seq1.right)`.
+    #[must_use]
+    fn difference(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> DifferenceReads<Self>
+    where
+        Self: Sized,
+    {
+        DifferenceReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Create new mappings for the gaps that are not covered by any of the input mappings.
+    ///
+    /// For `n` input mappings, there are `n + 1` gaps: before the first mapping, between
+    /// each pair of consecutive mappings, and after the last mapping, in order of the mapping start
+    /// positions. Gaps can be empty if mappings are adjacent or overlapping.
+    ///
+    /// The transform expression must have `n` input mappings and `n + 1` output mappings.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.a, seq1.b -> seq1.before, seq1.between, seq1.after)`.
+    #[must_use]
+    fn gaps(self, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> GapsReads<Self>
+    where
+        Self: Sized,
+    {
+        GapsReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Merge multiple mappings into one mapping that spans all of them.
+    ///
+    /// This is useful for cleaning up fragmented mappings after multiple cuts. The mappings are
+    /// only merged if there are no gaps between them, in which case the input mappings are
+    /// removed. Otherwise, the read is not changed. The `*` mapping cannot be merged.
+    ///
+    /// The transform expression must have one or more input mappings and one output mapping.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.a, seq1.b, seq1.c -> seq1.abc)`.
+    #[must_use]
+    fn merge_labels(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> MergeLabelsReads<Self>
+    where
+        Self: Sized,
+    {
+        MergeLabelsReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Strip the specified characters from both ends of a mapping, and create a new mapping for
+    /// the remaining interval.
+    ///
+    /// This is useful for cleaning up whitespace in read names, for example. If every character
+    /// is stripped, then the new mapping will be empty.
+    ///
+    /// The transform expression must have one input mapping and one output mapping.
+    ///
+    /// Example `transform_expr`: `tr!(name1.* -> name1.stripped)`.
+    #[must_use]
+    fn strip(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        chars: impl AsRef<[u8]>,
+    ) -> StripReads<Self>
+    where
+        Self: Sized,
+    {
+        StripReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            chars.as_ref().to_owned(),
+            None,
+        )
+    }
+
+    /// Strip the specified characters from only the left or right end of a mapping, and create
+    /// a new mapping for the remaining interval.
+    ///
+    /// The transform expression must have one input mapping and one output mapping.
+    ///
+    /// Example `transform_expr`: `tr!(name1.* -> name1.stripped)`.
+    #[must_use]
+    fn strip_end(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        chars: impl AsRef<[u8]>,
+        end: End,
+    ) -> StripReads<Self>
+    where
+        Self: Sized,
+    {
+        StripReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            chars.as_ref().to_owned(),
+            Some(end),
+        )
+    }
+
+    /// Compute the consensus of two equal-length mappings, position by position, and set a label
+    /// to the consensus sequence.
+    ///
+    /// At each position, the base with the higher quality score is picked. If the two bases agree,
+    /// then the new quality score is the sum of the two quality scores (capped at 41). Otherwise,
+    /// it is the difference between the two quality scores.
+    ///
+    /// Both input mappings must have quality scores and the same length. They can be in different
+    /// string types, like overlapping regions of the two mates of a paired-end read.
+    ///
+    /// The transform expression must have two input mappings and one output label.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.a, seq2.b -> seq1.a)`.
+    #[must_use]
+    fn consensus(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> ConsensusReads<Self>
+    where
+        Self: Sized,
+    {
+        ConsensusReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Compute the fraction of a string that is covered by the mappings corresponding to the
+    /// specified labels, and store it into a float attribute.
+    ///
+    /// Overlapping mappings are merged before summing, so no position is counted twice.
+    /// Missing labels are treated as covering nothing, so this can be used after matching
+    /// adapters or primers that are only present in some reads.
+    ///
+    /// The labels and the attribute must all have the same type. For example,
+    /// `coverage(sel!(), [label!(seq1.adapter), label!(seq1.primer)], attr!(seq1.*.adapter_frac))`.
+    #[must_use]
+    fn coverage(
+        self,
+        selector_expr: SelectorExpr,
+        labels: impl Into<Vec<Label>>,
+        attr: Attr,
+    ) -> CoverageReads<Self>
+    where
+        Self: Sized,
+    {
+        CoverageReads::new(self, selector_expr, labels.into(), attr)
+    }
+
+    /// Trim the mappings corresponding to the specified labels by modifying the underlying strings.
+    ///
+    /// When a mapping is trimmed, its length will be set to zero. All intersecting
+    /// mappings will also be adjusted accordingly for the shortening.
+    #[must_use]
+    fn trim(self, selector_expr: SelectorExpr, labels: impl Into<Vec<Label>>) -> TrimReads<Self>
+    where
+        Self: Sized,
+    {
+        TrimReads::new(self, selector_expr, labels.into())
+    }
+
+    /// Crop the mappings corresponding to the specified labels to at most `max_len` characters
+    /// by modifying the underlying strings.
+    ///
+    /// The characters are kept from the specified end, so `Left` keeps the first `max_len`
+    /// characters, like Trimmomatic's `CROP`. Unlike cutting at a fixed index, the number of
+    /// trimmed characters depends on the length of each mapping. Mappings that are not longer
+    /// than `max_len` are not changed. Quality scores and intersecting mappings are adjusted
+    /// like [`Reads::trim`].
+    #[must_use]
+    fn crop(
+        self,
+        selector_expr: SelectorExpr,
+        labels: impl Into<Vec<Label>>,
+        max_len: usize,
+        end: End,
+    ) -> CropReads<Self>
+    where
+        Self: Sized,
+    {
+        CropReads::new(self, selector_expr, labels.into(), max_len, end)
+    }
+
+    /// Replace all non-overlapping occurrences of `from` with `to` in the mappings corresponding
+    /// to the specified labels.
+    ///
+    /// Mappings that intersect with the modified mappings are adjusted for any shortening or
+    /// lengthening, like when setting a label. When replacing in a string with quality scores,
+    /// the replaced bases get the default quality score `I`, like missing quality scores in format
+    /// expressions. An empty `from` string does nothing.
+    #[must_use]
+    fn replace(
+        self,
+        selector_expr: SelectorExpr,
+        labels: impl Into<Vec<Label>>,
+        from: impl AsRef<[u8]>,
+        to: impl AsRef<[u8]>,
+    ) -> ReplaceReads<Self>
+    where
+        Self: Sized,
+    {
+        ReplaceReads::new(
+            self,
+            selector_expr,
+            labels.into(),
+            from.as_ref().to_owned(),
+            to.as_ref().to_owned(),
+        )
+    }
+
+    /// Convert the mappings corresponding to the specified labels to uppercase.
+    ///
+    /// Only ASCII letters are changed and quality scores are not modified.
+    #[must_use]
+    fn to_upper(
+        self,
+        selector_expr: SelectorExpr,
+        labels: impl Into<Vec<Label>>,
+    ) -> ToUpperReads<Self>
+    where
+        Self: Sized,
+    {
+        ToUpperReads::new(self, selector_expr, labels.into())
+    }
+
+    /// Convert the mappings corresponding to the specified labels to lowercase.
+    ///
+    /// Only ASCII letters are changed and quality scores are not modified.
+    #[must_use]
+    fn to_lower(
+        self,
+        selector_expr: SelectorExpr,
+        labels: impl Into<Vec<Label>>,
+    ) -> ToLowerReads<Self>
+    where
+        Self: Sized,
+    {
+        ToLowerReads::new(self, selector_expr, labels.into())
+    }
+
+    /// Reverse only the quality scores of the mappings corresponding to the specified labels.
+    ///
+    /// The sequences are not modified. This is mostly useful for debugging.
+    #[must_use]
+    fn reverse_qual(
+        self,
+        selector_expr: SelectorExpr,
+        labels: impl Into<Vec<Label>>,
+    ) -> ReverseQualReads<Self>
+    where
+        Self: Sized,
+    {
+        ReverseQualReads::new(self, selector_expr, labels.into())
+    }
+
+    /// Recalibrate the quality scores of the mappings corresponding to the specified labels
+    /// using a [`RecalibrationTable`].
+    ///
+    /// Each quality score is remapped based on its position in the mapping and its original
+    /// value. This is useful for applying a precomputed correction for systematic quality biases
+    /// of an instrument. Use the `*` label to index by the position in the read. Mappings without
+    /// quality scores cause an error.
+    #[must_use]
+    fn recalibrate_qual(
+        self,
+        selector_expr: SelectorExpr,
+        labels: impl Into<Vec<Label>>,
+        table: RecalibrationTable,
+    ) -> RecalibrateQualReads<Self>
+    where
+        Self: Sized,
+    {
+        RecalibrateQualReads::new(self, selector_expr, labels.into(), table)
+    }
+
+    /// Convert the quality scores of the specified string types to phred+33.
+    ///
+    /// If `offset` is specified, then the quality scores are assumed to use that offset, like
+    /// `Some(64)` for phred+64. Otherwise, the offset is detected once based on the range of the
+    /// quality scores of the selected reads in the first chunk with quality scores, and that offset
+    /// is used for all reads. Quality scores that could be either phred+33 or phred+64 are assumed
+    /// to be phred+33 and they are not changed. Selected reads with quality scores that contradict
+    /// the detected offset cause an error.
+    ///
+    /// Strings without quality scores are skipped.
+    #[must_use]
+    fn to_phred33(
+        self,
+        selector_expr: SelectorExpr,
+        str_types: impl Into<Vec<StrType>>,
+        offset: Option<u8>,
+    ) -> Phred33Reads<Self>
+    where
+        Self: Sized,
+    {
+        Phred33Reads::new(self, selector_expr, str_types.into(), offset)
+    }
+
+    /// Append the value of an attribute to the end of a string and create a new mapping for it.
+    ///
+    /// This allows computed attributes, like corrected barcodes, to be written to fastq records.
+    /// The string type of the new label is created if it does not exist. If the string has quality
+    /// scores, then the appended string will have quality scores of `qual`.
+    #[must_use]
+    fn attr_to_seq(
+        self,
+        selector_expr: SelectorExpr,
+        attr: Attr,
+        label: Label,
+        qual: char,
+    ) -> AttrToSeqReads<Self>
+    where
+        Self: Sized,
+    {
+        assert!(qual.is_ascii(), "Quality score must be an ASCII character");
+        AttrToSeqReads::new(self, selector_expr, attr, label, qual as u8)
+    }
+
+    /// Look up the substring of a mapping in an in-memory map, and store the mapped value into
+    /// an attribute.
+    ///
+    /// If the substring is not in the map, then the attribute is set to `default`, or false if
+    /// there is no default. This is useful for mapping barcodes to sample names, which can then
+    /// be used in output file names, like `"{seq1.*.sample}.fastq"`.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.barcode -> seq1.*.sample)`.
+    #[must_use]
+    fn map_lookup<K, V>(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        map: impl IntoIterator<Item = (K, V)>,
+        default: Option<&str>,
+    ) -> MapLookupReads<Self>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+        Self: Sized,
+    {
+        MapLookupReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            map.into_iter()
+                .map(|(k, v)| (k.as_ref().to_owned(), v.as_ref().to_owned()))
+                .collect(),
+            default.map(|d| d.as_bytes().to_owned()),
+        )
+    }
+
+    /// Set a label or attribute to the result of a format expression.
+    ///
+    /// After a label is set, its mapping and all other intersecting mappings will be adjusted accordingly
+    /// for any shortening or lengthening.
+    ///
+    /// When setting a label in a string with quality scores, the quality scores are also set.
+    /// By default, literals and attributes in the format expression use themselves as quality
+    /// scores and strings without quality scores use a default quality score. This can be changed
+    /// with [`SetReads::with_fill_qual`] and [`SetReads::with_require_qual`].
+    ///
+    /// Reads that do not have the label that is set or any of the labels and attributes used in
+    /// the format expression are skipped.
+    #[must_use]
+    fn set(
+        self,
+        selector_expr: SelectorExpr,
+        label_or_attr: impl Into<LabelOrAttr>,
+        format_expr: impl AsRef<str>,
+    ) -> SetReads<Self>
+    where
+        Self: Sized,
+    {
+        SetReads::new(
+            self,
+            selector_expr,
+            label_or_attr.into(),
+            FormatExpr::new(format_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the set operation: {e}")
+            }),
+        )
+    }
+
+    /// Set a label or attribute to one of two format expressions, depending on whether a read
+    /// matches a condition.
+    ///
+    /// If the read matches `cond_expr`, then `then_expr` is used, otherwise `else_expr` is used.
+    /// Only the chosen format expression is formatted, so labels or attributes that only appear
+    /// in the other format expression do not need to exist. Reads that do not have the label
+    /// that is set are skipped, like with [`Reads::set`].
+    ///
+    /// Example `cond_expr`: `sel!(seq1.*.rc)`.
+    ///
+    /// Example `then_expr` and `else_expr`: `"{name1.*}_rc"` and `"{name1.*}"`.
+    #[must_use]
+    fn set_if_else(
+        self,
+        selector_expr: SelectorExpr,
+        label_or_attr: impl Into<LabelOrAttr>,
+        cond_expr: SelectorExpr,
+        then_expr: impl AsRef<str>,
+        else_expr: impl AsRef<str>,
+    ) -> SetReads<Self>
+    where
+        Self: Sized,
+    {
+        SetReads::new_if_else(
+            self,
+            selector_expr,
+            label_or_attr.into(),
+            cond_expr,
+            FormatExpr::new(then_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the set_if_else operation: {e}")
+            }),
+            FormatExpr::new(else_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the set_if_else operation: {e}")
+            }),
+        )
+    }
+
+    /// Set a label or attribute to the first format expression whose labels and attributes all
+    /// exist in a read.
+    ///
+    /// This is useful when a mapping, like a barcode, can be in one of multiple places depending
+    /// on the read structure. If none of the format expressions can be formatted, then an error is
+    /// returned for the names that are missing in the last format expression. Reads that do not
+    /// have the label that is set are skipped, like with [`Reads::set`].
+    ///
+    /// Example `format_exprs`: `["{seq1.a}", "{seq1.b}"]`.
+    #[must_use]
+    fn set_coalesce<S: AsRef<str>>(
+        self,
+        selector_expr: SelectorExpr,
+        label_or_attr: impl Into<LabelOrAttr>,
+        format_exprs: impl IntoIterator<Item = S>,
+    ) -> SetReads<Self>
+    where
+        Self: Sized,
+    {
+        SetReads::new_coalesce(
+            self,
+            selector_expr,
+            label_or_attr.into(),
+            format_exprs
+                .into_iter()
+                .map(|e| {
+                    FormatExpr::new(e.as_ref().as_bytes()).unwrap_or_else(|e| {
+                        panic!(
+                            "Error in parsing format expression for the set_coalesce operation: {e}"
+                        )
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Match a regex pattern in a mapping.
+    ///
+    /// If named capture groups are used, then mappings are automatically created at the match
+    /// locations, labeled by the names specified in the regex.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.matched)`.
+    /// This will match the regex pattern two `seq1.*` and set `seq1.*.matched` to a boolean
+    /// indicating whether the regex matches.
+    #[must_use]
+    fn match_regex(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        regex: impl AsRef<str>,
+    ) -> MatchRegexReads<Self>
+    where
+        Self: Sized,
+    {
+        MatchRegexReads::new(self, selector_expr, transform_expr, regex.as_ref())
+    }
+
+    /// Match any one of multiple patterns in a mapping.
+    ///
+    /// Patterns are specified in YAML format:
+    /// ```
+    /// name: my_patterns
+    /// patterns:
+    ///   - pattern: AAAA
+    ///     some_extra_data1: !String "all As"
+    ///     some_extra_data2: !Bool true
+    ///   - pattern: TTTT
+    ///     some_extra_data1: !String "all Ts"
+    ///     some_extra_data2: !Bool false
+    /// ```
+    ///
+    /// Patterns can be arbitrary format expressions, so you can use any existing mappings or
+    /// attributes as patterns.
+    ///
+    /// You can also include arbitrary extra attributes, like `some_extra_data1` and
+    /// `some_extra_data2` in this example. The corresponding attributes for the matched pattern
+    /// will be stored into the input mapping.
+    ///
+    /// The transform expression must have one input mapping and the number of output mappings is
+    /// determined by the [`MatchType`].
+    ///
+    /// Example `transform_expr` for local-alignment-based pattern matching:
+    /// `tr!(seq1.* -> seq1.before, seq1.aligned, seq1.after)`.
+    /// The input mapping will get a new attribute (`seq1.*.my_patterns`) that is set to the pattern
+    /// that is matched. If no pattern matches, then it will be set to false.
+    /// Assuming pattern `AAAA` is matched, `seq1.*.some_extra_data1` will be set to `"all As"` and
+    /// `seq1.*.some_extra_data2` will be set to `true`.
+    ///
+    /// Lowercase (soft-masked) bases can be made unmatchable for exact and Hamming-distance-based
+    /// matching with [`MatchAnyReads::with_soft_mask`]. A minimum number of matching bases for prefix and
+    /// suffix alignment can be set with [`MatchAnyReads::with_min_overlap`], and the way sequence
+    /// identity is computed for alignment can be changed with
+    /// [`MatchAnyReads::with_identity_metric`]. The CIGAR string of the alignment can be stored
+    /// with [`MatchAnyReads::with_cigar_attr`] for debugging, and the index of the matched
+    /// pattern can be stored with [`MatchAnyReads::with_pattern_index_attr`].
+    ///
+    /// For Hamming-distance-based matching, `N` in patterns can be treated as a wildcard that
+    /// matches any base with [`MatchAnyReads::with_wildcards`]. This is useful for structured
+    /// barcodes like `NNNNCAGAGCNNNN`, where the fixed anchor drives the match. The runs of
+    /// wildcards can be labeled as new mappings, like UMIs between linkers, with
+    /// [`MatchAnyReads::with_wildcard_labels`].
+    #[must_use]
+    fn match_any(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        patterns_yaml: impl AsRef<str>,
+        match_type: MatchType,
+    ) -> MatchAnyReads<Self>
+    where
+        Self: Sized,
+    {
+        MatchAnyReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            Patterns::from_yaml(patterns_yaml.as_ref().as_bytes())
+                .unwrap_or_else(|e| panic!("Error in parsing patterns: {e}")),
+            match_type,
+        )
+    }
+
+    /// Match any one of the sequences in a FASTA file in a mapping.
+    ///
+    /// This is the same as [`Reads::match_any`], except the patterns are loaded from a FASTA
+    /// file, like a panel of adapters or primers. When a sequence is matched, its FASTA header
+    /// is stored into the `pattern_name` attribute of the input mapping, not the matched sequence.
+    ///
+    /// For example, primers can be trimmed while keeping the amplicon name for demultiplexing
+    /// by matching with [`MatchType::PrefixAln`] and then trimming the primer mapping:
+    /// ```ignore
+    /// reads
+    ///     .match_any_fasta(
+    ///         sel!(),
+    ///         tr!(seq1.* -> seq1.primer, seq1.rest),
+    ///         "primers.fasta",
+    ///         "amplicon",
+    ///         PrefixAln { identity: 0.9, overlap: 1.0 },
+    ///     )
+    ///     .trim(sel!(seq1.primer), [label!(seq1.primer)])
+    ///     .collect_fastq1(sel!(), "{seq1.*.amplicon}.fastq")
+    /// ```
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.primer, seq1.rest)`.
+    #[must_use]
+    fn match_any_fasta(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        fasta_file: impl AsRef<str>,
+        pattern_name: impl AsRef<str>,
+        match_type: MatchType,
+    ) -> MatchAnyReads<Self>
+    where
+        Self: Sized,
+    {
+        MatchAnyReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            Patterns::from_fasta(fasta_file, pattern_name)
+                .unwrap_or_else(|e| panic!("Error in loading patterns: {e}")),
+            match_type,
+        )
+    }
+
+    /// Match a prefix pattern at the start and a suffix pattern at the end of a mapping at the
+    /// same time.
+    ///
+    /// This is useful for reads framed by known sequences, like `PREFIX...insert...SUFFIX`.
+    /// Both anchors are matched with prefix/suffix alignment (like [`MatchType::PrefixAln`]
+    /// and [`MatchType::SuffixAln`]) with the same `identity` and `overlap` thresholds.
+    ///
+    /// Prefix and suffix patterns are specified in the same YAML format as [`Reads::match_any`].
+    /// The pattern names and extra attributes of both matched patterns are stored into the input
+    /// mapping.
+    ///
+    /// New mappings are only created if both anchors match and they do not overlap.
+    /// Otherwise, both pattern name attributes are set to false.
+    ///
+    /// The transform expression must have one input mapping and three output mappings.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.prefix, seq1.insert, seq1.suffix)`.
+    #[must_use]
+    fn match_anchors(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        prefix_patterns_yaml: impl AsRef<str>,
+        suffix_patterns_yaml: impl AsRef<str>,
+        identity: f64,
+        overlap: f64,
+    ) -> MatchAnchorsReads<Self>
+    where
+        Self: Sized,
+    {
+        MatchAnchorsReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            Patterns::from_yaml(prefix_patterns_yaml.as_ref().as_bytes())
+                .unwrap_or_else(|e| panic!("Error in parsing patterns: {e}")),
+            Patterns::from_yaml(suffix_patterns_yaml.as_ref().as_bytes())
+                .unwrap_or_else(|e| panic!("Error in parsing patterns: {e}")),
+            identity,
+            overlap,
+        )
+    }
+
+    /// Match a pattern in a mapping.
+    ///
+    /// The pattern can be an arbitrary format expression, so you can use any existing mappings or
+    /// attributes as patterns.
+    ///
+    /// The transform expression must have one input mapping and the number of output mappings is
     /// determined by the [`MatchType`].
     ///
     /// Example `transform_expr` for local-alignment-based pattern matching:
@@ -364,36 +1854,336 @@ pub trait Reads: Send + Sync {
     where
         Self: Sized,
     {
-        MatchAnyReads::new(
-            self,
-            selector_expr,
-            transform_expr,
-            Patterns::new(vec![FormatExpr::new(pattern.as_ref().as_bytes())
-                .unwrap_or_else(|e| {
-                    panic!("Error in parsing format expression for the match_one operation: {e}")
-                })]),
-            match_type,
+        MatchAnyReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            Patterns::new(vec![FormatExpr::new(pattern.as_ref().as_bytes())
+                .unwrap_or_else(|e| {
+                    panic!("Error in parsing format expression for the match_one operation: {e}")
+                })]),
+            match_type,
+        )
+    }
+
+    /// Match repeated characters from the left or right end of a mapping.
+    ///
+    /// The transform expression must have one input mapping and two output mappings.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.sequence, seq1.polya_tail)`.
+    #[must_use]
+    fn match_polyx(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        x: char,
+        end: End,
+        identity: f64,
+    ) -> MatchPolyXReads<Self>
+    where
+        Self: Sized,
+    {
+        MatchPolyXReads::new(self, selector_expr, transform_expr, x as u8, end, identity)
+    }
+
+    /// Demultiplex reads into one fastq file per sample, based on a barcode mapping and a sample
+    /// sheet.
+    ///
+    /// The sample sheet is a CSV file with a barcode and a sample name on each line, like
+    /// `ACGTACGT,sample1`. Empty lines and lines starting with `#` are skipped. Barcodes that are
+    /// not in the sample sheet are corrected to the closest barcode with at most `mismatch`
+    /// mismatches. If there is no such barcode or multiple barcodes are equally close, then the
+    /// read is undetermined.
+    ///
+    /// The sample name (or `undetermined`) is stored into the output attribute, and each read is
+    /// written to `{out_dir}/{sample}.fastq`. At the end, the number of reads for each sample in
+    /// the sample sheet, followed by the number of undetermined reads, is passed to `func`.
+    /// Samples cannot be named `undetermined`.
+    ///
+    /// Only read 1 is written out. Use [`Reads::demux2`] for paired-end reads.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.barcode -> seq1.*.sample)`.
+    #[must_use]
+    fn demux<F>(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        sample_sheet: impl AsRef<str>,
+        out_dir: impl AsRef<str>,
+        mismatch: usize,
+        func: F,
+    ) -> DemuxCollectReads<Self, F>
+    where
+        F: Fn(&[(&str, usize)]) + Send + Sync,
+        Self: Sized,
+    {
+        let reads = DemuxReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            sample_sheet.as_ref(),
+            mismatch,
+            func,
+        )
+        .unwrap_or_else(|e| panic!("Error in loading the sample sheet: {e}"));
+        let selector_expr = reads.selector_expr().clone();
+        let file_expr = reads.file_expr(out_dir.as_ref(), "");
+
+        CollectFastqReads::new1(reads, selector_expr, file_expr)
+    }
+
+    /// Demultiplex paired-end reads into two fastq files per sample, based on a barcode mapping
+    /// and a sample sheet.
+    ///
+    /// This is the same as [`Reads::demux`], except both records of each read are written, to
+    /// `{out_dir}/{sample}_R1.fastq` and `{out_dir}/{sample}_R2.fastq`.
+    ///
+    /// Example `transform_expr`: `tr!(index1.* -> index1.*.sample)`.
+    #[must_use]
+    fn demux2<F>(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        sample_sheet: impl AsRef<str>,
+        out_dir: impl AsRef<str>,
+        mismatch: usize,
+        func: F,
+    ) -> DemuxCollectReads<Self, F>
+    where
+        F: Fn(&[(&str, usize)]) + Send + Sync,
+        Self: Sized,
+    {
+        let reads = DemuxReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            sample_sheet.as_ref(),
+            mismatch,
+            func,
+        )
+        .unwrap_or_else(|e| panic!("Error in loading the sample sheet: {e}"));
+        let selector_expr = reads.selector_expr().clone();
+        let file_expr1 = reads.file_expr(out_dir.as_ref(), "_R1");
+        let file_expr2 = reads.file_expr(out_dir.as_ref(), "_R2");
+
+        CollectFastqReads::new2(reads, selector_expr, file_expr1, file_expr2)
+    }
+
+    /// Write reads into a fixed number of fastq files, based on the hash of a key mapping.
+    ///
+    /// Reads with the same key (for example, the same barcode) always end up in the same shard,
+    /// so each shard can be processed independently downstream. Only `num_shards` files are
+    /// opened, unlike demultiplexing by arbitrary values.
+    ///
+    /// The shard index (from `0` to `num_shards - 1`) is stored into the output attribute, and
+    /// each read is written to `file_expr` with `{shard}` replaced by the shard index.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.barcode -> seq1.*.shard)`.
+    ///
+    /// Example `file_expr`: `"out_{shard}.fastq"`.
+    ///
+    /// Only read 1 is written out. Use [`Reads::shard2`] for paired-end reads.
+    #[must_use]
+    fn shard(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        file_expr: impl AsRef<str>,
+        num_shards: usize,
+    ) -> ShardCollectReads<Self>
+    where
+        Self: Sized,
+    {
+        let reads = ShardReads::new(self, selector_expr, transform_expr, num_shards);
+        let selector_expr = reads.selector_expr().clone();
+        let file_expr = reads.file_expr(file_expr.as_ref());
+
+        CollectFastqReads::new1(reads, selector_expr, file_expr)
+    }
+
+    /// Write paired-end reads into a fixed number of pairs of fastq files, based on the hash of a
+    /// key mapping.
+    ///
+    /// This is the same as [`Reads::shard`], except both records of each read are written, to
+    /// `file_expr1` and `file_expr2` with `{shard}` replaced by the shard index.
+    ///
+    /// Example `file_expr1`: `"out_{shard}_R1.fastq"`.
+    #[must_use]
+    fn shard2(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        file_expr1: impl AsRef<str>,
+        file_expr2: impl AsRef<str>,
+        num_shards: usize,
+    ) -> ShardCollectReads<Self>
+    where
+        Self: Sized,
+    {
+        let reads = ShardReads::new(self, selector_expr, transform_expr, num_shards);
+        let selector_expr = reads.selector_expr().clone();
+        let file_expr1 = reads.file_expr(file_expr1.as_ref());
+        let file_expr2 = reads.file_expr(file_expr2.as_ref());
+
+        CollectFastqReads::new2(reads, selector_expr, file_expr1, file_expr2)
+    }
+
+    /// Find the index of the first base whose quality score (Phred+33) is below the threshold,
+    /// and store it into an unsigned integer attribute.
+    ///
+    /// The index is the length of the mapping if no base is below the threshold. This can be
+    /// used for custom quality trimming. Mappings without quality scores cause an error.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.first_low_qual)`.
+    #[must_use]
+    fn first_low_qual(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        threshold: u8,
+    ) -> FirstLowQualReads<Self>
+    where
+        Self: Sized,
+    {
+        FirstLowQualReads::new(self, selector_expr, transform_expr, threshold)
+    }
+
+    /// Count the number of consecutive bases from the 3' end whose quality score (Phred+33) is at
+    /// least the threshold, and store it into an unsigned integer attribute.
+    ///
+    /// This complements `first_low_qual` for custom quality trimming. Mappings without quality
+    /// scores cause an error.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.trailing_high_qual)`.
+    #[must_use]
+    fn trailing_high_qual(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        threshold: u8,
+    ) -> TrailingHighQualReads<Self>
+    where
+        Self: Sized,
+    {
+        TrailingHighQualReads::new(self, selector_expr, transform_expr, threshold)
+    }
+
+    /// Split reads into two output files based on the quality scores of a mapping.
+    ///
+    /// For each read, the mean or min quality score (Phred+33) of the mapping is compared to the
+    /// threshold, and the boolean result is stored in the output attribute. Reads that pass are
+    /// written to `pass_file_expr` and reads that fail are written to `fail_file_expr`. Mappings
+    /// without quality scores always fail.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.qual_pass)`.
+    ///
+    /// Only read 1 is written out. Use [`Reads::qual_split2`] for paired-end reads.
+    #[must_use]
+    fn qual_split(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        threshold: usize,
+        stat: QualStat,
+        pass_file_expr: impl AsRef<str>,
+        fail_file_expr: impl AsRef<str>,
+    ) -> QualSplitReads<Self>
+    where
+        Self: Sized,
+    {
+        let reads = QualPassReads::new(self, selector_expr, transform_expr, threshold, stat);
+        let (pass_selector_expr, fail_selector_expr) = reads.split_selector_exprs();
+
+        CollectFastqReads::new1(
+            CollectFastqReads::new1(
+                reads,
+                pass_selector_expr,
+                parse_qual_split_file_expr(pass_file_expr.as_ref()),
+            ),
+            fail_selector_expr,
+            parse_qual_split_file_expr(fail_file_expr.as_ref()),
+        )
+    }
+
+    /// Split paired-end reads into two pairs of output files based on the quality scores of a
+    /// mapping.
+    ///
+    /// This is the same as [`Reads::qual_split`], except both records of each read are written.
+    /// Reads that pass are written to the pair of files in `pass_file_exprs` and reads that fail
+    /// are written to the pair of files in `fail_file_exprs`.
+    ///
+    /// Example `pass_file_exprs`: `("pass_R1.fastq", "pass_R2.fastq")`.
+    #[must_use]
+    fn qual_split2(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        threshold: usize,
+        stat: QualStat,
+        pass_file_exprs: (impl AsRef<str>, impl AsRef<str>),
+        fail_file_exprs: (impl AsRef<str>, impl AsRef<str>),
+    ) -> QualSplitReads<Self>
+    where
+        Self: Sized,
+    {
+        let reads = QualPassReads::new(self, selector_expr, transform_expr, threshold, stat);
+        let (pass_selector_expr, fail_selector_expr) = reads.split_selector_exprs();
+
+        CollectFastqReads::new2(
+            CollectFastqReads::new2(
+                reads,
+                pass_selector_expr,
+                parse_qual_split_file_expr(pass_file_exprs.0.as_ref()),
+                parse_qual_split_file_expr(pass_file_exprs.1.as_ref()),
+            ),
+            fail_selector_expr,
+            parse_qual_split_file_expr(fail_file_exprs.0.as_ref()),
+            parse_qual_split_file_expr(fail_file_exprs.1.as_ref()),
         )
     }
 
-    /// Match repeated characters from the left or right end of a mapping.
+    /// Find a homopolymer tail, like a poly(A) tail, at the right end of a mapping, allowing up to
+    /// `max_errors` interrupting bases.
     ///
-    /// The transform expression must have one input mapping and two output mappings.
+    /// This is more tolerant of errors than [`Reads::match_polyx`], which makes it suitable for
+    /// long reads. The mapping is cut into the rest of the mapping and the tail if the tail has at
+    /// least `min_len` bases. The tail can then be removed with [`Reads::trim`]. The length of the
+    /// tail (or zero, if there is no tail) is stored into the output attribute.
     ///
-    /// Example `transform_expr`: `tr!(seq1.* -> seq1.sequence, seq1.polya_tail)`.
+    /// The transform expression must have one input mapping and three outputs: the two new
+    /// mappings and an attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.rest, seq1.poly_a, seq1.*.poly_a_len)`.
     #[must_use]
-    fn match_polyx(
+    fn trim_poly_tail(
         self,
         selector_expr: SelectorExpr,
         transform_expr: TransformExpr,
-        x: char,
-        end: End,
-        identity: f64,
-    ) -> MatchPolyXReads<Self>
+        base: char,
+        min_len: usize,
+        max_errors: usize,
+    ) -> TrimPolyTailReads<Self>
     where
         Self: Sized,
     {
-        MatchPolyXReads::new(self, selector_expr, transform_expr, x as u8, end, identity)
+        TrimPolyTailReads::new(
+            self,
+            selector_expr,
+            transform_expr,
+            base as u8,
+            min_len,
+            max_errors,
+        )
     }
 
     /// Output reads to a specified file.
@@ -425,6 +2215,10 @@ pub trait Reads: Send + Sync {
     ///
     /// Read 1 is written to `file_expr1` and read 2 is written to `file_expr2`.
     /// The reads will be interleaved if the files are the same.
+    ///
+    /// Both mates of each selected read must be present. Otherwise, an error is returned before
+    /// any records in the chunk are written, so the two files always have the same number of
+    /// records.
     #[must_use]
     fn collect_fastq2(
         self,
@@ -447,6 +2241,87 @@ pub trait Reads: Send + Sync {
         )
     }
 
+    /// Deinterleave paired-end reads into two separate files.
+    ///
+    /// The file paths are format expressions and they must be different.
+    ///
+    /// Read 1 (`name1` and `seq1`) is written to `file_expr1` and read 2 (`name2` and `seq2`) is
+    /// written to `file_expr2`. Selected reads that do not have read 2 will result in an error.
+    #[must_use]
+    fn deinterleave(
+        self,
+        selector_expr: SelectorExpr,
+        file_expr1: impl AsRef<str>,
+        file_expr2: impl AsRef<str>,
+    ) -> CollectFastqReads<Self>
+    where
+        Self: Sized,
+    {
+        assert_ne!(
+            file_expr1.as_ref(),
+            file_expr2.as_ref(),
+            "File paths must be different for the deinterleave operation"
+        );
+
+        CollectFastqReads::new2(
+            self,
+            selector_expr,
+            FormatExpr::new(file_expr1.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the deinterleave operation: {e}")
+            }),
+            FormatExpr::new(file_expr2.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the deinterleave operation: {e}")
+            }),
+        )
+    }
+
+    /// Pair reads from two inputs by matching read names.
+    ///
+    /// This is useful for reconstructing paired-end reads from two separate streams of
+    /// single-end reads that are not in the same order, such as after filtering each
+    /// mate separately.
+    ///
+    /// Reads are matched by `name1` up to the first whitespace, with any `/1` or `/2` suffix removed.
+    /// The `name1` and `seq1` strings of each read from `mate_reads` become the `name2` and `seq2`
+    /// strings of the paired read. Reads are buffered until their mate is found, so memory
+    /// usage grows with the number of unmatched reads. Reads that are not matched by
+    /// the end and unmatched reads with the same name in the same input will result in an error.
+    #[must_use]
+    fn pair_by_name<R2: Reads>(self, mate_reads: R2) -> PairByNameReads<Self, R2>
+    where
+        Self: Sized,
+    {
+        PairByNameReads::new(self, mate_reads)
+    }
+
+    /// Correct barcodes using the observed frequency of each barcode.
+    ///
+    /// Barcodes that occur at least `min_count` times are kept as is. Other barcodes are
+    /// corrected to the most frequent barcode that occurs at least `min_count` times and is within
+    /// `max_dist` mismatches. Barcodes that cannot be corrected, or that have multiple equally
+    /// frequent candidates, are marked with `false`.
+    ///
+    /// This requires two passes over the reads, so all reads are buffered in memory
+    /// when the first chunk is requested.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.barcode -> seq1.barcode.corrected)`.
+    /// This will set `seq1.barcode.corrected` to the corrected barcode.
+    #[must_use]
+    fn correct_by_frequency(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        min_count: usize,
+        max_dist: usize,
+    ) -> CorrectByFrequencyReads<Self>
+    where
+        Self: Sized,
+    {
+        CorrectByFrequencyReads::new(self, selector_expr, transform_expr, min_count, max_dist)
+    }
+
     /// Retain only the reads that are selected and discard the rest.
     #[must_use]
     fn retain(self, selector_expr: SelectorExpr) -> RetainReads<Self>
@@ -456,6 +2331,124 @@ pub trait Reads: Send + Sync {
         RetainReads::new(self, selector_expr)
     }
 
+    /// Filter out reads where the fraction of `N` bases in the mapping corresponding to the label
+    /// is greater than `max_frac`.
+    ///
+    /// Only selected reads are filtered. Reads without the mapping are kept.
+    #[must_use]
+    fn max_n_frac(
+        self,
+        selector_expr: SelectorExpr,
+        label: Label,
+        max_frac: f64,
+    ) -> MaxNFracReads<Self>
+    where
+        Self: Sized,
+    {
+        MaxNFracReads::new(self, selector_expr, label, max_frac)
+    }
+
+    /// Filter out reads where the mapping corresponding to the label is shorter than `min_len`,
+    /// and apply an arbitrary function on the number of dropped reads at the end.
+    ///
+    /// This is useful after trimming to discard reads that became too short and report how
+    /// many were discarded, like `|n| eprintln!("{n} reads discarded as too short")`.
+    ///
+    /// Only selected reads are filtered. Reads without the mapping are kept.
+    #[must_use]
+    fn min_length<F>(
+        self,
+        selector_expr: SelectorExpr,
+        label: Label,
+        min_len: usize,
+        func: F,
+    ) -> MinLengthReads<Self, F>
+    where
+        F: Fn(usize) + Send + Sync,
+        Self: Sized,
+    {
+        MinLengthReads::new(self, selector_expr, label, min_len, func)
+    }
+
+    /// Mark reads by storing a hash of the whole read into an attribute, so
+    /// [`Reads::retain_changed`] can later check whether the read was changed.
+    ///
+    /// This is useful for debugging, like checking whether an adapter trimmer actually
+    /// modifies any reads.
+    #[must_use]
+    fn mark(self, selector_expr: SelectorExpr, attr: Attr) -> MarkReads<Self>
+    where
+        Self: Sized,
+    {
+        MarkReads::new(self, selector_expr, attr)
+    }
+
+    /// Retain only the reads that were changed since they were marked with [`Reads::mark`].
+    ///
+    /// Any change to the strings, mappings, or attributes, other than the mark attribute,
+    /// counts as a change. Reads that were not marked are not retained.
+    #[must_use]
+    fn retain_changed(self, attr: Attr) -> RetainChangedReads<Self>
+    where
+        Self: Sized,
+    {
+        RetainChangedReads::new(self, attr)
+    }
+
+    /// Compute the canonical form of a mapping, which is the lexicographically smaller of the
+    /// mapping and its reverse complement, and store it into a bytes attribute.
+    ///
+    /// Using the canonical form as the key for deduplication collapses reads regardless of which
+    /// strand they were sequenced from.
+    ///
+    /// The transform expression must have one input mapping and one output attribute.
+    ///
+    /// Example `transform_expr`: `tr!(seq1.* -> seq1.*.canonical)`.
+    #[must_use]
+    fn canonical(
+        self,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+    ) -> CanonicalReads<Self>
+    where
+        Self: Sized,
+    {
+        CanonicalReads::new(self, selector_expr, transform_expr)
+    }
+
+    /// Discard selected reads whose key was probably already seen in an earlier read.
+    ///
+    /// The keys are stored in a Bloom filter sized for `expected_items` keys and a false positive
+    /// rate of `fp_rate`, so this uses much less memory than storing every key. The tradeoff is
+    /// that some unique reads are also discarded, at around the false positive rate (or more if
+    /// there are more keys than expected). Duplicates are never kept, except when the same key is
+    /// inserted at the same time by different threads.
+    ///
+    /// Reads that are not selected are always kept.
+    ///
+    /// Example `format_expr`: `"{seq1.*}"`.
+    #[must_use]
+    fn dedup_bloom(
+        self,
+        selector_expr: SelectorExpr,
+        format_expr: impl AsRef<str>,
+        expected_items: usize,
+        fp_rate: f64,
+    ) -> DedupBloomReads<Self>
+    where
+        Self: Sized,
+    {
+        DedupBloomReads::new(
+            self,
+            selector_expr,
+            FormatExpr::new(format_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the dedup_bloom operation: {e}")
+            }),
+            expected_items,
+            fp_rate,
+        )
+    }
+
     /// Take only the reads that have a record index inside the bounds.
     #[must_use]
     fn take<B>(self, bounds: B) -> TakeReads<Self, B>
@@ -466,6 +2459,43 @@ pub trait Reads: Send + Sync {
         TakeReads::new(self, bounds)
     }
 
+    /// Stop once the total length of the mapping corresponding to the label across the selected
+    /// reads reaches `max_bases`.
+    ///
+    /// The read that reaches the limit is kept, so slightly more than `max_bases` bases may be
+    /// output. This is the base-level analog of [`Reads::take`], which is useful for
+    /// normalizing sequencing depth across samples. Reads that are not selected are always kept
+    /// until the limit is reached.
+    ///
+    /// With multithreading, which reads are kept depends on how chunks are distributed across
+    /// threads.
+    #[must_use]
+    fn max_bases(
+        self,
+        selector_expr: SelectorExpr,
+        label: Label,
+        max_bases: usize,
+    ) -> MaxBasesReads<Self>
+    where
+        Self: Sized,
+    {
+        MaxBasesReads::new(self, selector_expr, label, max_bases)
+    }
+
+    /// Shuffle the order of reads within windows of at least `window` reads.
+    ///
+    /// This is useful for testing whether downstream tools depend on the order of reads.
+    ///
+    /// This is only deterministic when running with a single thread, since the reads that end up
+    /// in the same window depend on how chunks are distributed across threads.
+    #[must_use]
+    fn shuffle(self, window: usize, seed: u32) -> ShuffleReads<Self>
+    where
+        Self: Sized,
+    {
+        ShuffleReads::new(self, window, seed)
+    }
+
     /// Create two read iterators by cloning each read.
     ///
     /// You must use the [`run!()`](crate::run!) or [`run_with_threads!()`](crate::run_with_threads!) macros to run all the forks.
@@ -481,6 +2511,62 @@ pub trait Reads: Send + Sync {
         (left, right)
     }
 
+    /// Split reads into different read iterators based on the value of a format expression.
+    ///
+    /// Each selected read is sent to the iterator corresponding to the key that is equal to the
+    /// result of the format expression. Reads that are not selected or that do not match any key
+    /// are sent to the default iterator, which is returned separately.
+    ///
+    /// This allows different operations to be applied to different types of reads, like reads from
+    /// different protocols after matching patterns.
+    ///
+    /// Example `format_expr`: `"{seq1.*.my_patterns}"`.
+    ///
+    /// You must use the [`run!()`](crate::run!) or [`run_with_threads!()`](crate::run_with_threads!) macros to run all the branches.
+    ///
+    /// Reads for the other branches are buffered in memory until those branches are run. When a
+    /// branch does not get any reads from a chunk, more chunks are read until it does, so a
+    /// branch that rarely gets reads makes the other branches buffer many chunks. To bound the
+    /// memory usage, an error is returned if more than 2^20 reads are buffered per thread.
+    #[must_use]
+    fn branch<const N: usize>(
+        self,
+        selector_expr: SelectorExpr,
+        format_expr: impl AsRef<str>,
+        keys: [&str; N],
+    ) -> ([BranchReads<Self>; N], BranchReads<Self>)
+    where
+        Self: Sized,
+    {
+        let reads = Arc::new(self);
+        let state = Arc::new(BranchState::new(
+            selector_expr,
+            FormatExpr::new(format_expr.as_ref().as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the branch operation: {e}")
+            }),
+            keys.iter().map(|k| k.as_bytes().to_owned()).collect(),
+        ));
+        let branches =
+            std::array::from_fn(|i| BranchReads::new(Arc::clone(&reads), Arc::clone(&state), i));
+        let default = BranchReads::new(reads, state, N);
+        (branches, default)
+    }
+
+    /// Apply an arbitrary function on the number of reads in each chunk that reaches this point
+    /// in the iterator chain.
+    ///
+    /// This is cheaper than [`Reads::for_each`] for reporting progress or metrics, since it is only
+    /// called once per chunk. With multithreading, the function may be called from different
+    /// threads at the same time.
+    #[must_use]
+    fn on_chunk<F>(self, func: F) -> OnChunkReads<Self, F>
+    where
+        F: Fn(usize) + Send + Sync,
+        Self: Sized,
+    {
+        OnChunkReads::new(self, func)
+    }
+
     /// Compute the runtime (in seconds) of all operations before this in the iterator chain.
     ///
     /// The runtime is summed across all threads.
@@ -495,6 +2581,37 @@ pub trait Reads: Send + Sync {
         TimeReads::new(self, func)
     }
 
+    /// Apply a reusable stage of operations to the read iterator.
+    ///
+    /// The stage is a function that takes a read iterator and returns a new one, so the same
+    /// stage can be defined once and applied at multiple points, like on each branch after
+    /// [`Reads::branch`] or each fork after [`Reads::fork`]:
+    /// ```ignore
+    /// fn trim<R: Reads>(reads: R) -> impl Reads {
+    ///     reads
+    ///         .match_polyx(sel!(), tr!(seq1.* -> seq1.trimmed, seq1.polya_tail), 'A', Right, 0.9)
+    ///         .trim(sel!(), [label!(seq1.polya_tail)])
+    /// }
+    ///
+    /// let (a, b) = iter_fastq1("in.fastq", 256)?.fork();
+    /// let a = a.apply(trim).collect_fastq1(sel!(), "a.fastq");
+    /// let b = b.apply(trim).collect_fastq1(sel!(), "b.fastq");
+    /// ```
+    ///
+    /// Each application of the stage creates new operations, so operations with state (like
+    /// counts, deduplication, or callbacks) are not shared between the places where the stage is
+    /// applied. To share state, create it outside of the stage (for example, in an `Arc`) and
+    /// move a reference into the stage.
+    #[must_use]
+    fn apply<F, O>(self, stage: F) -> O
+    where
+        F: FnOnce(Self) -> O,
+        O: Reads,
+        Self: Sized,
+    {
+        stage(self)
+    }
+
     /// Box the read iterator by creating a `Box<dyn Reads>`.
     ///
     /// This allows iterators to be dynamically chained at runtime.
@@ -631,6 +2748,65 @@ impl<R: Reads + ?Sized> Reads for Box<R> {
     }
 }
 
+thread_local! {
+    static LENIENT_ERRORS: RefCell<Option<Arc<AtomicUsize>>> = const { RefCell::new(None) };
+}
+
+/// Run `f` on the current thread in lenient mode, counting errors in the shared `errors`.
+fn with_lenient_errors<T>(errors: &Arc<AtomicUsize>, f: impl FnOnce() -> T) -> T {
+    let prev = LENIENT_ERRORS.replace(Some(Arc::clone(errors)));
+    let res = f();
+    LENIENT_ERRORS.set(prev);
+    res
+}
+
+/// Apply a fallible operation to each read in a chunk.
+///
+/// When running with [`Reads::run_lenient`] or [`Reads::run_lenient_with_threads`], reads that
+/// fail with an [`Error::NameError`] are printed to stderr and passed on as they are, and the
+/// rest of the chunk is still processed.
+pub(crate) fn try_for_each_read(
+    reads: &mut Vec<Read>,
+    mut f: impl FnMut(&mut Read) -> Result<()>,
+) -> Result<()> {
+    try_retain_reads(reads, |read| f(read).map(|_| true))
+}
+
+/// Retain the reads in a chunk that a fallible predicate returns `true` for.
+///
+/// Errors are handled the same way as [`try_for_each_read`].
+pub(crate) fn try_retain_reads(
+    reads: &mut Vec<Read>,
+    mut f: impl FnMut(&mut Read) -> Result<bool>,
+) -> Result<()> {
+    let mut res = Ok(());
+
+    reads.retain_mut(|read| {
+        if res.is_err() {
+            return true;
+        }
+
+        match f(read) {
+            Ok(keep) => keep,
+            Err(e @ Error::NameError { .. }) if LENIENT_ERRORS.with_borrow(Option::is_some) => {
+                eprintln!("Error when running: {e}");
+                LENIENT_ERRORS.with_borrow(|errors| {
+                    errors
+                        .as_ref()
+                        .map(|errors| errors.fetch_add(1, Ordering::Relaxed))
+                });
+                true
+            }
+            Err(e) => {
+                res = Err(e);
+                true
+            }
+        }
+    });
+
+    res
+}
+
 pub use MatchType::*;
 pub use Threshold::*;
 
@@ -643,6 +2819,9 @@ pub use Threshold::*;
 /// identity computation. This is important for local alignment, where the start and end of the
 /// pattern can be excluded from the alignment, and prefix/suffix alignment, where the start/end
 /// of the pattern can be excluded from the alignment (prefix/suffix "overhang").
+///
+/// The denominator of the sequence identity can be changed with
+/// [`MatchAnyReads::with_identity_metric`].
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum MatchType {
     /// Exact match.
@@ -729,6 +2908,92 @@ impl MatchType {
     }
 }
 
+/// Denominator used for computing the sequence identity of an alignment.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum IdentityMetric {
+    /// Number of alignment columns (matches, mismatches, insertions, and deletions), which is
+    /// the default.
+    Columns,
+    /// Length of the pattern.
+    PatternLen,
+    /// Length of the shorter of the pattern and the aligned string.
+    MinLen,
+}
+
+impl IdentityMetric {
+    pub(crate) fn identity(
+        &self,
+        matches: usize,
+        columns: usize,
+        pattern_len: usize,
+        string_len: usize,
+    ) -> f64 {
+        let total = match self {
+            IdentityMetric::Columns => columns,
+            IdentityMetric::PatternLen => pattern_len,
+            IdentityMetric::MinLen => pattern_len.min(string_len),
+        };
+        (matches as f64) / (total as f64)
+    }
+}
+
+/// What to do when a cut index is out of range for a mapping.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OutOfRange {
+    /// Clamp the index to the bounds of the mapping, which is the default.
+    Clamp,
+    /// Skip cutting the read, so the new mappings will not exist.
+    Skip,
+    /// Return an error.
+    Error,
+}
+
+/// Statistic computed over the quality scores of a mapping.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum QualStat {
+    Mean,
+    Min,
+}
+
+impl QualStat {
+    /// Compute the statistic over Phred+33 quality scores.
+    ///
+    /// Empty quality scores result in zero.
+    pub fn get(&self, qual: &[u8]) -> usize {
+        use QualStat::*;
+        match self {
+            Mean => mean_qual_score(qual).round() as usize,
+            Min => qual
+                .iter()
+                .map(|&q| q.saturating_sub(b'!') as usize)
+                .min()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Where to look for a substring in a mapping.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SubstringKind {
+    /// The substring can be anywhere in the mapping.
+    Contains,
+    /// The mapping must start with the substring.
+    StartsWith,
+    /// The mapping must end with the substring.
+    EndsWith,
+}
+
+/// How to combine whether each label or attribute is present in a read.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PresentKind {
+    /// Whether all of them are present, as a bool.
+    All,
+    /// Whether any of them are present, as a bool.
+    Any,
+    /// How many of them are present, as a uint.
+    Num,
+}
+
 /// Either a count or a fraction.
 ///
 /// Typically used for specifying the similarity threshold when matching patterns.
@@ -748,3 +3013,80 @@ impl Threshold {
         }
     }
 }
+
+/// Operation on a numeric attribute.
+///
+/// For binary operations, both operands must be unsigned integers or both must be floats, and
+/// the result has the same type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum NumericOp {
+    /// Remainder after dividing by the operand.
+    Rem(Operand),
+    /// Smaller of the attribute and the operand.
+    Min(Operand),
+    /// Larger of the attribute and the operand.
+    Max(Operand),
+    /// Bound the attribute to be between the lower and upper bound operands.
+    ///
+    /// A lower bound that is greater than the upper bound causes an error.
+    Clamp(Operand, Operand),
+    /// Round a float down to an unsigned integer.
+    ///
+    /// Rounding to a negative value causes an error, so any float below zero is an error.
+    Floor,
+    /// Round a float up to an unsigned integer.
+    ///
+    /// Rounding to a negative value causes an error, but floats above -1, like -0.5, round up
+    /// to 0.
+    Ceil,
+    /// Round a float to the nearest unsigned integer, with ties rounded to the nearest even
+    /// integer for reproducibility, like 2.5 to 2 and 3.5 to 4.
+    ///
+    /// Rounding to a negative value causes an error, but floats that round to zero, like -0.5,
+    /// give 0.
+    Round,
+}
+
+/// Operand of a numeric operation, which is either an attribute or a constant.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    Attr(Attr),
+    UInt(usize),
+    Float(f64),
+}
+
+impl Operand {
+    pub(crate) fn eval(&self, read: &Read) -> std::result::Result<Data, NameError> {
+        use Operand::*;
+        match self {
+            Attr(a) => Ok(read.data(a.str_type, a.label, a.attr)?.clone()),
+            UInt(x) => Ok(Data::UInt(*x)),
+            Float(x) => Ok(Data::Float(*x)),
+        }
+    }
+
+    pub(crate) fn attr(&self) -> Option<&Attr> {
+        match self {
+            Operand::Attr(a) => Some(a),
+            _ => None,
+        }
+    }
+}
+
+impl From<Attr> for Operand {
+    fn from(a: Attr) -> Self {
+        Operand::Attr(a)
+    }
+}
+
+impl From<usize> for Operand {
+    fn from(x: usize) -> Self {
+        Operand::UInt(x)
+    }
+}
+
+impl From<f64> for Operand {
+    fn from(x: f64) -> Self {
+        Operand::Float(x)
+    }
+}
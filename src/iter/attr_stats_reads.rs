@@ -0,0 +1,219 @@
+use rustc_hash::FxHashMap;
+use thread_local::*;
+
+use std::cell::RefCell;
+use std::fmt;
+
+use crate::iter::*;
+
+pub struct AttrStatsReads<R: Reads, F: Fn(&AttrStats) + Send + Sync> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    attr: Attr,
+    top_n: usize,
+    values: ThreadLocal<RefCell<Values>>,
+    func: F,
+}
+
+#[derive(Default)]
+struct Values {
+    /// Histogram of numeric values, keyed by the bits of the float.
+    numeric: FxHashMap<u64, usize>,
+    other: FxHashMap<Vec<u8>, usize>,
+}
+
+/// Summary statistics of the values of an attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttrStats {
+    /// Statistics of the unsigned integer and float values, if there are any.
+    pub numeric: Option<NumericStats>,
+    /// The most common bytes and boolean values with their counts, from most to least common.
+    pub top: Vec<(Vec<u8>, usize)>,
+    /// The number of distinct bytes and boolean values.
+    pub distinct: usize,
+}
+
+/// Summary statistics of numeric values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+}
+
+impl<R: Reads, F: Fn(&AttrStats) + Send + Sync> AttrStatsReads<R, F> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, attr: Attr, top_n: usize, func: F) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require([attr.clone().into()]),
+            attr,
+            top_n,
+            values: ThreadLocal::new(),
+            func,
+        }
+    }
+}
+
+impl<R: Reads, F: Fn(&AttrStats) + Send + Sync> Reads for AttrStatsReads<R, F> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+        let mut values = self
+            .values
+            .get_or(|| RefCell::new(Values::default()))
+            .borrow_mut();
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "summarizing attribute values",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let data = read
+                .data(self.attr.str_type, self.attr.label, self.attr.attr)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "summarizing attribute values",
+                })?;
+
+            match data {
+                Data::UInt(x) => *values.numeric.entry((*x as f64).to_bits()).or_insert(0) += 1,
+                Data::Float(x) => *values.numeric.entry(x.to_bits()).or_insert(0) += 1,
+                Data::Bytes(x) => *values.other.entry(x.clone()).or_insert(0) += 1,
+                Data::Bool(x) => *values.other.entry(x.to_string().into_bytes()).or_insert(0) += 1,
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()?;
+
+        // merge values from all threads
+        let mut numeric = FxHashMap::default();
+        let mut other = FxHashMap::default();
+        for values in self.values.iter_mut() {
+            let values = values.get_mut();
+            for (v, c) in values.numeric.drain() {
+                *numeric.entry(v).or_insert(0) += c;
+            }
+            for (v, c) in values.other.drain() {
+                *other.entry(v).or_insert(0) += c;
+            }
+        }
+
+        let mut numeric = numeric
+            .into_iter()
+            .map(|(v, c)| (f64::from_bits(v), c))
+            .collect::<Vec<_>>();
+        numeric.sort_unstable_by(|(a, _), (b, _)| a.total_cmp(b));
+        let distinct = other.len();
+        let mut top = other.into_iter().collect::<Vec<_>>();
+        // break ties by value so the output is deterministic
+        top.sort_unstable_by(|(v1, c1), (v2, c2)| c2.cmp(c1).then_with(|| v1.cmp(v2)));
+        top.truncate(self.top_n);
+
+        (self.func)(&AttrStats {
+            numeric: NumericStats::from_histogram(&numeric),
+            top,
+            distinct,
+        });
+        Ok(())
+    }
+}
+
+impl NumericStats {
+    /// Compute the summary from a histogram of `(value, count)` pairs sorted by value, or `None`
+    /// if there are no values.
+    fn from_histogram(histogram: &[(f64, usize)]) -> Option<Self> {
+        let count = histogram.iter().map(|&(_, c)| c).sum::<usize>();
+
+        if count == 0 {
+            return None;
+        }
+
+        // quantiles are the lower value when they fall between two values
+        let quantile = |q: f64| {
+            let idx = ((count - 1) as f64 * q) as usize;
+            let mut seen = 0;
+            histogram
+                .iter()
+                .find(|&&(_, c)| {
+                    seen += c;
+                    seen > idx
+                })
+                .unwrap()
+                .0
+        };
+
+        Some(Self {
+            count,
+            min: histogram[0].0,
+            max: histogram[histogram.len() - 1].0,
+            mean: histogram.iter().map(|&(v, c)| v * c as f64).sum::<f64>() / (count as f64),
+            q1: quantile(0.25),
+            median: quantile(0.5),
+            q3: quantile(0.75),
+        })
+    }
+}
+
+impl fmt::Display for AttrStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(n) = &self.numeric {
+            writeln!(
+                f,
+                "count: {}, min: {}, max: {}, mean: {:.2}, q1: {}, median: {}, q3: {}",
+                n.count, n.min, n.max, n.mean, n.q1, n.median, n.q3
+            )?;
+        }
+
+        if !self.top.is_empty() {
+            writeln!(f, "distinct values: {}", self.distinct)?;
+        }
+
+        for (v, c) in &self.top {
+            writeln!(f, "{}\t{}", utf8(v), c)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numeric_stats() {
+        // 1, 2, 2, 2, 3, 10
+        let stats = NumericStats::from_histogram(&[(1.0, 1), (2.0, 3), (3.0, 1), (10.0, 1)]);
+        assert_eq!(
+            stats,
+            Some(NumericStats {
+                count: 6,
+                min: 1.0,
+                max: 10.0,
+                mean: 20.0 / 6.0,
+                q1: 2.0,
+                median: 2.0,
+                q3: 2.0,
+            })
+        );
+        assert_eq!(NumericStats::from_histogram(&[]), None);
+    }
+}
@@ -0,0 +1,71 @@
+use crate::iter::*;
+
+pub struct AttrToSeqReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    attr: Attr,
+    label: Label,
+    qual: u8,
+}
+
+impl<R: Reads> AttrToSeqReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, attr: Attr, label: Label, qual: u8) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require([attr.clone().into()]),
+            attr,
+            label,
+            qual,
+        }
+    }
+}
+
+impl<R: Reads> Reads for AttrToSeqReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "converting attributes to sequences",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let new_str = read
+                .data(self.attr.str_type, self.attr.label, self.attr.attr)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "converting attributes to sequences",
+                })?
+                .to_string()
+                .into_bytes();
+
+            read.append(
+                self.label.str_type,
+                Some(self.label.label),
+                &new_str,
+                self.qual,
+            )
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "converting attributes to sequences",
+            })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,122 @@
+use thread_local::*;
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::iter::*;
+
+pub struct BaseCompositionReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    max_len: usize,
+    file: String,
+    counts: ThreadLocal<RefCell<Vec<[u64; 5]>>>,
+}
+
+impl<R: Reads> BaseCompositionReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        label: Label,
+        max_len: usize,
+        file: String,
+    ) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require([label.clone().into()]),
+            label,
+            max_len,
+            file,
+            counts: ThreadLocal::new(),
+        }
+    }
+
+    fn write_tsv(&self, counts: &[[u64; 5]]) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(&self.file)?);
+        writeln!(writer, "pos\tA\tC\tG\tT\tN")?;
+
+        for (i, c) in counts.iter().enumerate() {
+            let total = c.iter().sum::<u64>().max(1) as f64;
+            write!(writer, "{}", i)?;
+            for &x in c {
+                write!(writer, "\t{:.6}", x as f64 / total)?;
+            }
+            writeln!(writer)?;
+        }
+
+        writer.flush()
+    }
+}
+
+impl<R: Reads> Reads for BaseCompositionReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+        let mut counts = self
+            .counts
+            .get_or(|| RefCell::new(vec![[0u64; 5]; self.max_len]))
+            .borrow_mut();
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing base composition",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let seq = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing base composition",
+                })?;
+
+            for (c, &b) in counts.iter_mut().zip(seq) {
+                let i = match b {
+                    b'A' | b'a' => 0,
+                    b'C' | b'c' => 1,
+                    b'G' | b'g' => 2,
+                    b'T' | b't' => 3,
+                    _ => 4,
+                };
+                c[i] += 1;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()?;
+
+        // merge counts from all threads
+        let mut merged = vec![[0u64; 5]; self.max_len];
+        for counts in self.counts.iter_mut() {
+            for (a, b) in merged.iter_mut().zip(counts.get_mut().iter()) {
+                a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+            }
+        }
+
+        // only report positions that are covered by at least one read
+        let len = merged
+            .iter()
+            .rposition(|c| c.iter().any(|&x| x > 0))
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        self.write_tsv(&merged[..len]).map_err(|e| Error::FileIo {
+            file: self.file.clone(),
+            source: Box::new(e),
+        })
+    }
+}
@@ -34,7 +34,7 @@ impl<R: Reads> Reads for BernoulliReads<R> {
             .wrapping_add(reads.first().map(|r| r.first_idx() as u64).unwrap_or(0u64));
         let mut rng = Xoshiro256PlusPlus::seed_from_u64(seed);
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -44,7 +44,7 @@ impl<R: Reads> Reads for BernoulliReads<R> {
                     context: "generating bernoulli random samples",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             let rand_bool = self.bernoulli.sample(&mut rng);
@@ -54,7 +54,9 @@ impl<R: Reads> Reads for BernoulliReads<R> {
                 .data_mut(self.attr.str_type, self.attr.label, self.attr.attr)
                 .unwrap_or_else(|e| panic!("Error generating bernoulli random samples: {e}")) =
                 Data::Bool(rand_bool);
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -0,0 +1,132 @@
+use thread_local::*;
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use crate::iter::*;
+
+/// Maximum number of reads that each thread buffers for the other branches while waiting for
+/// reads in one branch.
+const MAX_BUFFERED_READS: usize = 1 << 20;
+
+pub struct BranchState {
+    selector_expr: SelectorExpr,
+    format_expr: FormatExpr,
+    keys: Vec<Vec<u8>>,
+    buf: ThreadLocal<RefCell<Vec<Option<Vec<Read>>>>>,
+}
+
+impl BranchState {
+    pub fn new(selector_expr: SelectorExpr, format_expr: FormatExpr, keys: Vec<Vec<u8>>) -> Self {
+        Self {
+            selector_expr: selector_expr.require(format_expr.required_names()),
+            format_expr,
+            keys,
+            buf: ThreadLocal::new(),
+        }
+    }
+
+    /// Index of the branch for a read, where the last branch is the default branch.
+    fn branch_idx(&self, read: &Read) -> Result<usize> {
+        if !(self
+            .selector_expr
+            .matches(read)
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "branching reads",
+            })?)
+        {
+            return Ok(self.keys.len());
+        }
+
+        let key = self
+            .format_expr
+            .format(read, false)
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "branching reads",
+            })?;
+
+        Ok(self
+            .keys
+            .iter()
+            .position(|k| *k == key)
+            .unwrap_or(self.keys.len()))
+    }
+}
+
+pub struct BranchReads<R: Reads> {
+    reads: Arc<R>,
+    state: Arc<BranchState>,
+    idx: usize,
+}
+
+impl<R: Reads> BranchReads<R> {
+    pub fn new(reads: Arc<R>, state: Arc<BranchState>, idx: usize) -> Self {
+        Self { reads, state, idx }
+    }
+}
+
+impl<R: Reads> Reads for BranchReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let buf = self
+            .state
+            .buf
+            .get_or(|| RefCell::new(vec![None; self.state.keys.len() + 1]));
+        let mut b = buf.borrow_mut();
+
+        loop {
+            if let Some(reads) = b[self.idx].take() {
+                if !reads.is_empty() {
+                    return Ok(reads);
+                }
+            }
+
+            let reads = self.reads.next_chunk()?;
+
+            if reads.is_empty() {
+                return Ok(reads);
+            }
+
+            let mut branches = vec![Vec::new(); self.state.keys.len() + 1];
+
+            for read in reads {
+                let i = self.state.branch_idx(&read)?;
+                branches[i].push(read);
+            }
+
+            let mut res = Vec::new();
+
+            // save the reads for the other branches
+            for (i, branch) in branches.into_iter().enumerate() {
+                if i == self.idx {
+                    res = branch;
+                } else {
+                    b[i].get_or_insert_with(Vec::new).extend(branch);
+                }
+            }
+
+            // an empty chunk means that there are no more reads, so only return once this branch
+            // gets some reads
+            if !res.is_empty() {
+                return Ok(res);
+            }
+
+            let buffered = b.iter().flatten().map(|reads| reads.len()).sum::<usize>();
+
+            if buffered > MAX_BUFFERED_READS {
+                return Err(Error::BranchBufferFull(MAX_BUFFERED_READS, self.idx));
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(reads) = Arc::get_mut(&mut self.reads) {
+            reads.finish()
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::iter::*;
+use crate::seq_utils::reverse_complement;
+
+pub struct CanonicalReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+}
+
+impl<R: Reads> CanonicalReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(1, 1, "computing the canonical sequence");
+        transform_expr.check_same_str_type("computing the canonical sequence");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when computing the canonical sequence"),
+            }),
+        }
+    }
+}
+
+impl<R: Reads> Reads for CanonicalReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the canonical sequence",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let seq = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the canonical sequence",
+                })?;
+            let rc = reverse_complement(seq);
+            let canonical = if rc.as_slice() < seq {
+                rc
+            } else {
+                seq.to_owned()
+            };
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error computing the canonical sequence: {e}")) =
+                Data::Bytes(canonical);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,105 @@
+use thread_local::*;
+
+use std::cell::RefCell;
+
+use crate::iter::*;
+
+pub struct CardinalityReads<R: Reads, F: Fn(f64) + Send + Sync> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    format_expr: FormatExpr,
+    registers: ThreadLocal<RefCell<Vec<u8>>>,
+    func: F,
+}
+
+/// Number of bits of the hash used to pick a register.
+const PRECISION: u32 = 14;
+const NUM_REGISTERS: usize = 1 << PRECISION;
+
+impl<R: Reads, F: Fn(f64) + Send + Sync> CardinalityReads<R, F> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, format_expr: FormatExpr, func: F) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require(format_expr.required_names()),
+            format_expr,
+            registers: ThreadLocal::new(),
+            func,
+        }
+    }
+}
+
+impl<R: Reads, F: Fn(f64) + Send + Sync> Reads for CardinalityReads<R, F> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+        let mut registers = self
+            .registers
+            .get_or(|| RefCell::new(vec![0u8; NUM_REGISTERS]))
+            .borrow_mut();
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "estimating cardinality",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let key = self
+                .format_expr
+                .format(read, false)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "estimating cardinality",
+                })?;
+            let hash = hash_bytes(&key);
+
+            let idx = (hash >> (64 - PRECISION)) as usize;
+            let rank = ((hash << PRECISION).leading_zeros() + 1).min(64 - PRECISION + 1) as u8;
+            registers[idx] = registers[idx].max(rank);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()?;
+
+        // merge the sketches from all threads
+        let mut merged = vec![0u8; NUM_REGISTERS];
+        for registers in self.registers.iter_mut() {
+            merged
+                .iter_mut()
+                .zip(registers.get_mut().iter())
+                .for_each(|(a, &b)| *a = (*a).max(b));
+        }
+
+        (self.func)(estimate(&merged));
+        Ok(())
+    }
+}
+
+/// HyperLogLog estimate with the small range correction.
+fn estimate(registers: &[u8]) -> f64 {
+    let m = registers.len() as f64;
+    let alpha = 0.7213 / (1.0 + 1.079 / m);
+    let sum = registers
+        .iter()
+        .map(|&r| 2.0f64.powi(-(r as i32)))
+        .sum::<f64>();
+    let raw = alpha * m * m / sum;
+
+    let zeros = registers.iter().filter(|&&r| r == 0).count();
+    if raw <= 2.5 * m && zeros > 0 {
+        m * (m / zeros as f64).ln()
+    } else {
+        raw
+    }
+}
@@ -0,0 +1,113 @@
+use crate::iter::*;
+
+pub struct ToUpperReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    labels: Vec<Label>,
+}
+
+impl<R: Reads> ToUpperReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, labels: Vec<Label>) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require(labels.iter().cloned().map(LabelOrAttr::Label)),
+            labels,
+        }
+    }
+}
+
+impl<R: Reads> Reads for ToUpperReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "converting to uppercase",
+                })?)
+            {
+                return Ok(());
+            }
+
+            self.labels
+                .iter()
+                .try_for_each(|l| {
+                    read.substring_mut(l.str_type, l.label)
+                        .map(|s| s.make_ascii_uppercase())
+                })
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "converting to uppercase",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+pub struct ToLowerReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    labels: Vec<Label>,
+}
+
+impl<R: Reads> ToLowerReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, labels: Vec<Label>) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require(labels.iter().cloned().map(LabelOrAttr::Label)),
+            labels,
+        }
+    }
+}
+
+impl<R: Reads> Reads for ToLowerReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "converting to lowercase",
+                })?)
+            {
+                return Ok(());
+            }
+
+            self.labels
+                .iter()
+                .try_for_each(|l| {
+                    read.substring_mut(l.str_type, l.label)
+                        .map(|s| s.make_ascii_lowercase())
+                })
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "converting to lowercase",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -6,6 +6,7 @@ use rustc_hash::FxHashMap;
 
 use flate2::{write::GzEncoder, Compression};
 
+use crate::bgzf::BgzfWriter;
 use crate::fastq::*;
 use crate::iter::*;
 
@@ -15,6 +16,12 @@ pub struct CollectFastqReads<R: Reads> {
     file_expr1: FormatExpr,
     file_expr2: Option<FormatExpr>,
     file_writers: Mutex<FxHashMap<Vec<u8>, Arc<Mutex<dyn Write + Send>>>>,
+    gzip_level: Compression,
+    buffer_size: usize,
+    header_expr1: Option<FormatExpr>,
+    header_expr2: Option<FormatExpr>,
+    mate_suffixes: bool,
+    swap_mates: bool,
 }
 
 impl<R: Reads> CollectFastqReads<R> {
@@ -25,6 +32,12 @@ impl<R: Reads> CollectFastqReads<R> {
             file_expr1: file_expr,
             file_expr2: None,
             file_writers: Mutex::new(FxHashMap::default()),
+            gzip_level: Compression::default(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            header_expr1: None,
+            header_expr2: None,
+            mate_suffixes: false,
+            swap_mates: false,
         }
     }
 
@@ -40,10 +53,109 @@ impl<R: Reads> CollectFastqReads<R> {
             file_expr1,
             file_expr2: Some(file_expr2),
             file_writers: Mutex::new(FxHashMap::default()),
+            gzip_level: Compression::default(),
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            header_expr1: None,
+            header_expr2: None,
+            mate_suffixes: false,
+            swap_mates: false,
         }
     }
+
+    /// Set the compression level (0-9) for output files ending in `.gz`, `.bgz`, or `.bgzf`.
+    ///
+    /// Lower levels are faster and higher levels compress better. The default is 6.
+    pub fn with_gzip_level(mut self, level: u32) -> Self {
+        assert!(level <= 9, "Gzip compression level must be between 0 and 9");
+        self.gzip_level = Compression::new(level);
+        self
+    }
+
+    /// Set the size of the write buffer for each output file.
+    ///
+    /// Larger buffers reduce the number of writes to disk. The default is 8 KiB.
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Build the header of each output fastq record from a format expression, instead of
+    /// using the read's name.
+    ///
+    /// For paired-end reads, the same expression is used for both records.
+    ///
+    /// Selected reads that are missing a mapping or attribute used in the header expression
+    /// cause an error.
+    pub fn with_header_format(self, header_expr: impl AsRef<str>) -> Self {
+        self.with_header_format2(header_expr.as_ref(), header_expr.as_ref())
+    }
+
+    /// Build the headers of the first and second output fastq records of paired-end reads
+    /// from different format expressions.
+    pub fn with_header_format2(
+        mut self,
+        header_expr1: impl AsRef<str>,
+        header_expr2: impl AsRef<str>,
+    ) -> Self {
+        let parse = |header_expr: &str| {
+            FormatExpr::new(header_expr.as_bytes()).unwrap_or_else(|e| {
+                panic!("Error in parsing format expression for the fastq record header: {e}")
+            })
+        };
+        self.header_expr1 = Some(parse(header_expr1.as_ref()));
+        self.header_expr2 = Some(parse(header_expr2.as_ref()));
+        self
+    }
+
+    /// Append `/1` and `/2` to the headers of the first and second output fastq records of
+    /// paired-end reads.
+    pub fn with_mate_suffixes(mut self) -> Self {
+        self.mate_suffixes = true;
+        self
+    }
+
+    /// Set which mate of paired-end reads is written to each output file.
+    ///
+    /// The default order is `[1, 2]`. With `[2, 1]`, the second mate is written to the first
+    /// output file and the first mate is written to the second output file, without modifying
+    /// the reads.
+    pub fn with_mate_order(mut self, order: [usize; 2]) -> Self {
+        self.swap_mates = match order {
+            [1, 2] => false,
+            [2, 1] => true,
+            _ => panic!("Expected the mate order to be [1, 2] or [2, 1]"),
+        };
+        self
+    }
+
+    fn header(
+        &self,
+        read: &Read,
+        name: &[u8],
+        header_expr: &Option<FormatExpr>,
+        suffix: &[u8],
+    ) -> Result<Vec<u8>> {
+        let mut header = match header_expr {
+            Some(header_expr) => header_expr
+                .format(read, false)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "collecting into fastq file(s)",
+                })?,
+            None => name.to_owned(),
+        };
+
+        if self.mate_suffixes {
+            header.extend_from_slice(suffix);
+        }
+
+        Ok(header)
+    }
 }
 
+const DEFAULT_BUFFER_SIZE: usize = 8 * 1024;
+
 impl<R: Reads> Reads for CollectFastqReads<R> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let reads = self.reads.next_chunk()?;
@@ -68,14 +180,23 @@ impl<R: Reads> Reads for CollectFastqReads<R> {
                             std::fs::create_dir_all(parent)?;
                         }
 
-                        let writer: Arc<Mutex<dyn Write + Send>> = if file_path.ends_with(".gz") {
-                            Arc::new(Mutex::new(BufWriter::new(GzEncoder::new(
-                                File::create(file_path)?,
-                                Compression::default(),
-                            ))))
-                        } else {
-                            Arc::new(Mutex::new(BufWriter::new(File::create(file_path)?)))
-                        };
+                        let writer: Arc<Mutex<dyn Write + Send>> =
+                            if file_path.ends_with(".bgz") || file_path.ends_with(".bgzf") {
+                                Arc::new(Mutex::new(BufWriter::with_capacity(
+                                    self.buffer_size,
+                                    BgzfWriter::new(File::create(file_path)?, self.gzip_level),
+                                )))
+                            } else if file_path.ends_with(".gz") {
+                                Arc::new(Mutex::new(BufWriter::with_capacity(
+                                    self.buffer_size,
+                                    GzEncoder::new(File::create(file_path)?, self.gzip_level),
+                                )))
+                            } else {
+                                Arc::new(Mutex::new(BufWriter::with_capacity(
+                                    self.buffer_size,
+                                    File::create(file_path)?,
+                                )))
+                            };
                         locked_writers.push(Arc::clone(e.insert(writer)));
                     }
                 }
@@ -127,24 +248,39 @@ impl<R: Reads> Reads for CollectFastqReads<R> {
         }
 
         if self.file_expr2.is_some() {
-            for (locked_writer, read) in locked_writers.chunks(2).zip(
-                reads
-                    .iter()
-                    .filter(|r| self.selector_expr.matches(r).unwrap()),
-            ) {
-                let (record1, record2) = read.to_fastq2().map_err(|e| Error::NameError {
-                    source: e,
-                    read: read.clone(),
-                    context: "collecting into fastq file(s)",
-                })?;
+            // check that both mates are present for all reads before writing any records, so
+            // the output files never have different numbers of records
+            let records = reads
+                .iter()
+                .filter(|r| self.selector_expr.matches(r).unwrap())
+                .map(|read| {
+                    let (mut record1, mut record2) =
+                        read.to_fastq2().map_err(|e| Error::NameError {
+                            source: e,
+                            read: read.clone(),
+                            context: "collecting into fastq file(s)",
+                        })?;
+                    if self.swap_mates {
+                        std::mem::swap(&mut record1, &mut record2);
+                    }
+                    let header1 = self.header(read, record1.0, &self.header_expr1, b"/1")?;
+                    let header2 = self.header(read, record2.0, &self.header_expr2, b"/2")?;
+                    Ok((
+                        (header1, record1.1, record1.2),
+                        (header2, record2.1, record2.2),
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            for (locked_writer, (record1, record2)) in locked_writers.chunks(2).zip(records) {
                 // interleave records if the same file is specified twice
                 {
                     let mut writer1 = locked_writer[0].lock().unwrap();
-                    write_fastq_record(&mut *writer1, record1);
+                    write_fastq_record(&mut *writer1, (&record1.0, record1.1, record1.2));
                 }
                 {
                     let mut writer2 = locked_writer[1].lock().unwrap();
-                    write_fastq_record(&mut *writer2, record2);
+                    write_fastq_record(&mut *writer2, (&record2.0, record2.1, record2.2));
                 }
             }
         } else {
@@ -153,8 +289,10 @@ impl<R: Reads> Reads for CollectFastqReads<R> {
                     .iter()
                     .filter(|r| self.selector_expr.matches(r).unwrap()),
             ) {
+                let (name, seq, qual) = read.to_fastq1();
+                let header = self.header(read, name, &self.header_expr1, b"")?;
                 let mut writer = locked_writer.lock().unwrap();
-                write_fastq_record(&mut *writer, read.to_fastq1());
+                write_fastq_record(&mut *writer, (&header, seq, qual));
             }
         }
 
@@ -165,3 +303,64 @@ impl<R: Reads> Reads for CollectFastqReads<R> {
         self.reads.finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::*;
+    use crate::fastq::Origin;
+    use crate::*;
+
+    use std::sync::{Arc, Mutex};
+
+    struct ChunkReads(Mutex<Option<Vec<Read>>>);
+
+    impl Reads for ChunkReads {
+        fn next_chunk(&self) -> Result<Vec<Read>> {
+            Ok(self.0.lock().unwrap().take().unwrap_or_default())
+        }
+
+        fn finish(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_collect_fastq2_unpaired() {
+        let origin = Arc::new(Origin::Bytes);
+        let paired = Read::from_fastq2(
+            b"a",
+            b"ACGT",
+            b"IIII",
+            Arc::clone(&origin),
+            0,
+            b"a",
+            b"TGCA",
+            b"IIII",
+            Arc::clone(&origin),
+            0,
+        );
+        let unpaired = Read::from_fastq1(b"b", b"ACGT", b"IIII", origin, 1);
+
+        let dir = std::env::temp_dir();
+        let file1 = dir.join(format!("unpaired_{}_R1.fastq", std::process::id()));
+        let file2 = dir.join(format!("unpaired_{}_R2.fastq", std::process::id()));
+
+        let res = ChunkReads(Mutex::new(Some(vec![paired, unpaired])))
+            .collect_fastq2(sel!(), file1.to_str().unwrap(), file2.to_str().unwrap())
+            .run();
+
+        assert!(matches!(
+            res,
+            Err(Error::NameError {
+                source: NameError::NotInRead(_),
+                ..
+            })
+        ));
+        // the paired read in the same chunk is not written either
+        assert!(std::fs::read(&file1).unwrap_or_default().is_empty());
+        assert!(std::fs::read(&file2).unwrap_or_default().is_empty());
+
+        let _ = std::fs::remove_file(file1);
+        let _ = std::fs::remove_file(file2);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::iter::*;
+
+pub struct ConsensusReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label1: Label,
+    label2: Label,
+    new_label: Label,
+}
+
+impl<R: Reads> ConsensusReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(2, 1, "computing the consensus of mappings in reads");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label1: transform_expr.before()[0].clone(),
+            label2: transform_expr.before()[1].clone(),
+            new_label: match transform_expr.after()[0].clone() {
+                Some(LabelOrAttr::Label(l)) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when computing the consensus of mappings in reads"),
+            },
+        }
+    }
+}
+
+impl<R: Reads> Reads for ConsensusReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the consensus of mappings in reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let (new_str, new_qual) =
+                consensus(read, &self.label1, &self.label2).map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the consensus of mappings in reads",
+                })?;
+
+            read.set(
+                self.new_label.str_type,
+                self.new_label.label,
+                &new_str,
+                Some(&new_qual),
+            )
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "computing the consensus of mappings in reads",
+            })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+fn consensus(
+    read: &Read,
+    label1: &Label,
+    label2: &Label,
+) -> std::result::Result<(Vec<u8>, Vec<u8>), NameError> {
+    let (seq1, qual1) = read.substring_with_qual(label1.str_type, label1.label)?;
+    let (seq2, qual2) = read.substring_with_qual(label2.str_type, label2.label)?;
+
+    if seq1.len() != seq2.len() {
+        return Err(NameError::UnequalLen(seq1.len(), seq2.len()));
+    }
+
+    let mut new_str = Vec::with_capacity(seq1.len());
+    let mut new_qual = Vec::with_capacity(seq1.len());
+
+    for i in 0..seq1.len() {
+        let q1 = qual1[i].saturating_sub(b'!');
+        let q2 = qual2[i].saturating_sub(b'!');
+
+        if seq1[i].eq_ignore_ascii_case(&seq2[i]) {
+            // agreeing bases reinforce each other
+            new_str.push(seq1[i]);
+            new_qual.push(b'!' + (q1 + q2).min(MAX_QUAL));
+        } else if q1 >= q2 {
+            // disagreeing bases lower the confidence of the higher quality base
+            new_str.push(seq1[i]);
+            new_qual.push(b'!' + (q1 - q2));
+        } else {
+            new_str.push(seq2[i]);
+            new_qual.push(b'!' + (q2 - q1));
+        }
+    }
+
+    Ok((new_str, new_qual))
+}
+
+const MAX_QUAL: u8 = 41;
@@ -0,0 +1,123 @@
+use memchr::memmem;
+
+use crate::iter::*;
+
+pub struct ContainsReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    needle: Vec<u8>,
+    kind: SubstringKind,
+}
+
+impl<R: Reads> ContainsReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        needle: Vec<u8>,
+        kind: SubstringKind,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "checking for a substring");
+        transform_expr.check_same_str_type("checking for a substring");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when checking for a substring"),
+            }),
+            needle,
+            kind,
+        }
+    }
+}
+
+impl<R: Reads> Reads for ContainsReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "checking for a substring",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let string = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "checking for a substring",
+                })?;
+
+            use SubstringKind::*;
+            let res = match self.kind {
+                Contains => memmem::find(string, &self.needle).is_some(),
+                StartsWith => string.starts_with(&self.needle),
+                EndsWith => string.ends_with(&self.needle),
+            };
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error checking for a substring: {e}")) =
+                Data::Bool(res);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn names(reads: Vec<Read>) -> Vec<Vec<u8>> {
+        reads.iter().map(|r| r.to_fastq1().0.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_contains_empty_needle() {
+        let reads = iter_fastq1_bytes(b"@a\nACGT\n+\nIIII\n@b\nAAAA\n+\nIIII\n")
+            .unwrap()
+            .cut(sel!(), tr!(seq1.* -> seq1.empty, seq1.rest), 0)
+            .contains(sel!(), tr!(seq1.* -> seq1.*.has), "")
+            .contains(sel!(), tr!(seq1.empty -> seq1.empty.has), "")
+            .retain(sel!(seq1.*.has & seq1.empty.has))
+            .run_collect_reads()
+            .unwrap();
+        assert_eq!(names(reads), [b"a".to_vec(), b"b".to_vec()]);
+    }
+
+    #[test]
+    fn test_contains_selector() {
+        let reads = iter_fastq1_bytes(b"@a\nACGT\n+\nIIII\n@b\nAAAA\n+\nIIII\n")
+            .unwrap()
+            .contains(sel!(), tr!(seq1.* -> seq1.*.has), "CG")
+            .retain(sel!(!seq1.*.has))
+            .run_collect_reads()
+            .unwrap();
+        assert_eq!(names(reads), [b"b".to_vec()]);
+    }
+}
@@ -0,0 +1,192 @@
+use rustc_hash::FxHashMap;
+
+use std::sync::{Arc, Mutex};
+
+use crate::iter::*;
+
+pub struct CorrectByFrequencyReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    min_count: usize,
+    max_dist: usize,
+    buf: Mutex<Option<Buf>>,
+}
+
+struct Buf {
+    chunks: Vec<Vec<Read>>,
+    corrected: Arc<FxHashMap<Vec<u8>, Option<Vec<u8>>>>,
+}
+
+impl<R: Reads> CorrectByFrequencyReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        min_count: usize,
+        max_dist: usize,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "correcting barcodes by frequency");
+        transform_expr.check_same_str_type("correcting barcodes by frequency");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when correcting barcodes by frequency"),
+            }),
+            min_count,
+            max_dist,
+            buf: Mutex::new(None),
+        }
+    }
+
+    fn fill_buf(&self) -> Result<Buf> {
+        let mut chunks = Vec::new();
+        let mut counts = FxHashMap::default();
+
+        loop {
+            let reads = self.reads.next_chunk()?;
+
+            if reads.is_empty() {
+                break;
+            }
+
+            for read in &reads {
+                if !(self
+                    .selector_expr
+                    .matches(read)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "correcting barcodes by frequency",
+                    })?)
+                {
+                    continue;
+                }
+
+                let barcode = read
+                    .substring(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "correcting barcodes by frequency",
+                    })?;
+                *counts.entry(barcode.to_owned()).or_insert(0usize) += 1;
+            }
+
+            chunks.push(reads);
+        }
+
+        // reverse so chunks can be popped in their original order
+        chunks.reverse();
+
+        let frequent = counts
+            .iter()
+            .filter(|(_, &c)| c >= self.min_count)
+            .collect::<Vec<_>>();
+        let corrected: FxHashMap<_, _> = counts
+            .iter()
+            .map(|(barcode, &count)| {
+                if count >= self.min_count {
+                    return (barcode.clone(), Some(barcode.clone()));
+                }
+
+                let threshold = barcode.len().saturating_sub(self.max_dist);
+                let mut best: Option<(&[u8], usize)> = None;
+                let mut ambiguous = false;
+
+                for &(b, &c) in &frequent {
+                    if hamming(barcode, b, threshold).is_none() {
+                        continue;
+                    }
+
+                    match best {
+                        Some((_, best_c)) if c < best_c => (),
+                        Some((_, best_c)) if c == best_c => ambiguous = true,
+                        _ => {
+                            best = Some((b, c));
+                            ambiguous = false;
+                        }
+                    }
+                }
+
+                let res = if ambiguous {
+                    None
+                } else {
+                    best.map(|(b, _)| b.to_owned())
+                };
+                (barcode.clone(), res)
+            })
+            .collect();
+
+        Ok(Buf {
+            chunks,
+            corrected: Arc::new(corrected),
+        })
+    }
+}
+
+impl<R: Reads> Reads for CorrectByFrequencyReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let (mut reads, corrected) = {
+            let mut buf = self.buf.lock().unwrap();
+
+            if buf.is_none() {
+                *buf = Some(self.fill_buf()?);
+            }
+
+            let buf = buf.as_mut().unwrap();
+            (
+                buf.chunks.pop().unwrap_or_default(),
+                Arc::clone(&buf.corrected),
+            )
+        };
+
+        let Some(attr) = &self.attr else {
+            return Ok(reads);
+        };
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "correcting barcodes by frequency",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let barcode = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "correcting barcodes by frequency",
+                })?;
+            let data = match corrected.get(barcode) {
+                Some(Some(b)) => Data::Bytes(b.clone()),
+                _ => Data::Bool(false),
+            };
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error correcting barcodes by frequency: {e}")) = data;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
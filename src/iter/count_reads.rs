@@ -25,11 +25,11 @@ impl<R: Reads, F: Fn(&[usize]) + Send + Sync> CountReads<R, F> {
 
 impl<R: Reads, F: Fn(&[usize]) + Send + Sync> Reads for CountReads<R, F> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
-        let reads = self.reads.next_chunk()?;
+        let mut reads = self.reads.next_chunk()?;
 
-        for read in &reads {
+        try_for_each_read(&mut reads, |read| {
             for (c, s) in self.counts.iter().zip(&self.selector_exprs) {
-                if s.matches(&read).map_err(|e| Error::NameError {
+                if s.matches(read).map_err(|e| Error::NameError {
                     source: e,
                     read: read.clone(),
                     context: "counting reads",
@@ -37,7 +37,9 @@ impl<R: Reads, F: Fn(&[usize]) + Send + Sync> Reads for CountReads<R, F> {
                     c.fetch_add(1, Ordering::Relaxed);
                 }
             }
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -0,0 +1,102 @@
+use crate::inline_string::InlineString;
+use crate::iter::*;
+
+pub struct CoverageReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    labels: Vec<Label>,
+    attr: Attr,
+}
+
+impl<R: Reads> CoverageReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, labels: Vec<Label>, attr: Attr) -> Self {
+        assert!(
+            labels.iter().all(|l| l.str_type == attr.str_type),
+            "Expected the labels to have the same type as the attribute when computing the coverage of labels"
+        );
+
+        Self {
+            reads,
+            selector_expr,
+            labels,
+            attr,
+        }
+    }
+}
+
+impl<R: Reads> Reads for CoverageReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the coverage of labels",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let total = read
+                .mapping(self.attr.str_type, InlineString::new(b"*"))
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the coverage of labels",
+                })?
+                .len;
+
+            let mut intervals = self
+                .labels
+                .iter()
+                .filter_map(|l| read.mapping(l.str_type, l.label).ok())
+                .map(|m| (m.start, m.start + m.len))
+                .collect::<Vec<_>>();
+            intervals.sort_unstable();
+
+            // merge overlapping intervals to avoid double counting
+            let mut covered = 0;
+            let mut curr: Option<(usize, usize)> = None;
+
+            for (start, end) in intervals {
+                match &mut curr {
+                    Some((_, curr_end)) if start <= *curr_end => *curr_end = (*curr_end).max(end),
+                    _ => {
+                        if let Some((s, e)) = curr {
+                            covered += e - s;
+                        }
+                        curr = Some((start, end));
+                    }
+                }
+            }
+
+            if let Some((s, e)) = curr {
+                covered += e - s;
+            }
+
+            let frac = if total == 0 {
+                0.0
+            } else {
+                (covered as f64) / (total as f64)
+            };
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(self.attr.str_type, self.attr.label, self.attr.attr)
+                .unwrap_or_else(|e| panic!("Error computing the coverage of labels: {e}")) =
+                Data::Float(frac);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,64 @@
+use crate::iter::*;
+
+pub struct CropReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    labels: Vec<Label>,
+    max_len: usize,
+    end: End,
+}
+
+impl<R: Reads> CropReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        labels: Vec<Label>,
+        max_len: usize,
+        end: End,
+    ) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require(labels.iter().cloned().map(LabelOrAttr::Label)),
+            labels,
+            max_len,
+            end,
+        }
+    }
+}
+
+impl<R: Reads> Reads for CropReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "cropping reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            self.labels
+                .iter()
+                .try_for_each(|l| read.crop(l.str_type, l.label, self.max_len, self.end))
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "cropping reads",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
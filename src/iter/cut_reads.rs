@@ -7,6 +7,7 @@ pub struct CutReads<R: Reads> {
     new_label1: Option<Label>,
     new_label2: Option<Label>,
     cut_idx: EndIdx,
+    out_of_range: OutOfRange,
 }
 
 impl<R: Reads> CutReads<R> {
@@ -21,7 +22,7 @@ impl<R: Reads> CutReads<R> {
 
         Self {
             reads,
-            selector_expr,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
             cut_label: transform_expr.before()[0].clone(),
             new_label1: transform_expr.after()[0].clone().map(|l| match l {
                 LabelOrAttr::Label(l) => l,
@@ -32,15 +33,22 @@ impl<R: Reads> CutReads<R> {
                 _ => panic!("Expected type.label after the \"->\" in the transform expression when cutting reads"),
             }),
             cut_idx,
+            out_of_range: OutOfRange::Clamp,
         }
     }
+
+    /// Set what to do when the cut index is beyond the length of the mapping.
+    pub fn with_out_of_range(mut self, out_of_range: OutOfRange) -> Self {
+        self.out_of_range = out_of_range;
+        self
+    }
 }
 
 impl<R: Reads> Reads for CutReads<R> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let mut reads = self.reads.next_chunk()?;
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -50,7 +58,31 @@ impl<R: Reads> Reads for CutReads<R> {
                     context: "cutting reads",
                 })?)
             {
-                continue;
+                return Ok(());
+            }
+
+            if self.out_of_range != OutOfRange::Clamp {
+                let len = read
+                    .mapping(self.cut_label.str_type, self.cut_label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "cutting reads",
+                    })?
+                    .len;
+                let (LeftEnd(idx) | RightEnd(idx)) = self.cut_idx;
+
+                if idx > len {
+                    if self.out_of_range == OutOfRange::Skip {
+                        return Ok(());
+                    }
+
+                    return Err(Error::NameError {
+                        source: NameError::OutOfRange(idx, len),
+                        read: read.clone(),
+                        context: "cutting reads",
+                    });
+                }
             }
 
             read.cut(
@@ -65,7 +97,9 @@ impl<R: Reads> Reads for CutReads<R> {
                 read: read.clone(),
                 context: "cutting read",
             })?;
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -0,0 +1,57 @@
+use crate::iter::*;
+
+pub struct CycleReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    attr: Attr,
+    n: usize,
+}
+
+impl<R: Reads> CycleReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, attr: Attr, n: usize) -> Self {
+        assert!(n >= 1, "Cycle length must be greater than zero");
+
+        Self {
+            reads,
+            selector_expr,
+            attr,
+            n,
+        }
+    }
+}
+
+impl<R: Reads> Reads for CycleReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing cycle positions",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let pos = read.read_idx() % self.n;
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(self.attr.str_type, self.attr.label, self.attr.attr)
+                .unwrap_or_else(|e| panic!("Error computing cycle positions: {e}")) =
+                Data::UInt(pos);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::iter::*;
+
+pub struct DedupBloomReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    format_expr: FormatExpr,
+    bits: Vec<AtomicU64>,
+    num_hashes: usize,
+}
+
+impl<R: Reads> DedupBloomReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        format_expr: FormatExpr,
+        expected_items: usize,
+        fp_rate: f64,
+    ) -> Self {
+        assert!(
+            fp_rate > 0.0 && fp_rate < 1.0,
+            "False positive rate must be between 0 and 1"
+        );
+
+        // optimal bloom filter parameters
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-n * fp_rate.ln() / (ln2 * ln2)).ceil() as usize;
+        let num_words = num_bits.div_ceil(64);
+        let num_hashes = ((num_words * 64) as f64 / n * ln2).round().max(1.0) as usize;
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(format_expr.required_names()),
+            format_expr,
+            bits: (0..num_words).map(|_| AtomicU64::new(0)).collect(),
+            num_hashes,
+        }
+    }
+
+    /// Insert the key and return whether it was probably inserted before.
+    fn insert(&self, key: &[u8]) -> bool {
+        let num_bits = (self.bits.len() * 64) as u64;
+        let h1 = hash_bytes(key);
+        let h2 = hash_bytes(&h1.to_le_bytes()) | 1;
+        let mut seen = true;
+
+        // double hashing to generate the bit indexes
+        for i in 0..self.num_hashes as u64 {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % num_bits;
+            let mask = 1u64 << (bit % 64);
+            let prev = self.bits[(bit / 64) as usize].fetch_or(mask, Ordering::Relaxed);
+            seen &= (prev & mask) != 0;
+        }
+
+        seen
+    }
+}
+
+impl<R: Reads> Reads for DedupBloomReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        loop {
+            let mut reads = self.reads.next_chunk()?;
+
+            if reads.is_empty() {
+                return Ok(reads);
+            }
+
+            try_retain_reads(&mut reads, |read| {
+                if !(self
+                    .selector_expr
+                    .matches(read)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "deduplicating reads",
+                    })?)
+                {
+                    return Ok(true);
+                }
+
+                let key = self
+                    .format_expr
+                    .format(read, false)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "deduplicating reads",
+                    })?;
+
+                Ok(!self.insert(&key))
+            })?;
+
+            // an empty chunk means that there are no more reads, so only return once some reads
+            // are kept
+            if !reads.is_empty() {
+                return Ok(reads);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,235 @@
+use rustc_hash::FxHashMap;
+use thread_local::*;
+
+use std::cell::RefCell;
+
+use crate::iter::*;
+
+/// Reads that are demultiplexed into one output file per sample.
+pub type DemuxCollectReads<R, F> = CollectFastqReads<DemuxReads<R, F>>;
+
+/// Table from each barcode to the index of its sample.
+type BarcodeTable = FxHashMap<Vec<u8>, usize>;
+
+pub struct DemuxReads<R: Reads, F: Fn(&[(&str, usize)]) + Send + Sync> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Attr,
+    samples: Vec<String>,
+    barcodes: BarcodeTable,
+    mismatch: usize,
+    counts: ThreadLocal<RefCell<Vec<usize>>>,
+    func: F,
+}
+
+impl<R: Reads, F: Fn(&[(&str, usize)]) + Send + Sync> DemuxReads<R, F> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        sample_sheet: &str,
+        mismatch: usize,
+        func: F,
+    ) -> Result<Self> {
+        transform_expr.check_size(1, 1, "demultiplexing reads");
+        transform_expr.check_same_str_type("demultiplexing reads");
+
+        let (barcodes, samples) = read_sample_sheet(sample_sheet)?;
+
+        Ok(Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: match transform_expr.after()[0].clone() {
+                Some(LabelOrAttr::Attr(a)) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when demultiplexing reads"),
+            },
+            samples,
+            barcodes,
+            mismatch,
+            counts: ThreadLocal::new(),
+            func,
+        })
+    }
+
+    pub(crate) fn selector_expr(&self) -> &SelectorExpr {
+        &self.selector_expr
+    }
+
+    /// Output file for each sample, like `{out_dir}/{sample}_R1.fastq` with the suffix `_R1`.
+    pub(crate) fn file_expr(&self, out_dir: &str, suffix: &str) -> FormatExpr {
+        let file_expr = format!(
+            "{}/{{{}.{}.{}}}{}.fastq",
+            out_dir, self.attr.str_type, self.attr.label, self.attr.attr, suffix
+        );
+
+        FormatExpr::new(file_expr.as_bytes()).unwrap_or_else(|e| {
+            panic!("Error in parsing format expression for the demux operation: {e}")
+        })
+    }
+
+    /// Get the index of the sample with the closest barcode, or `None` if there is no barcode
+    /// within the mismatch threshold or multiple barcodes are equally close.
+    fn assign(&self, barcode: &[u8]) -> Option<usize> {
+        if let Some(&idx) = self.barcodes.get(barcode) {
+            return Some(idx);
+        }
+
+        if self.mismatch == 0 {
+            return None;
+        }
+
+        let threshold = barcode.len().saturating_sub(self.mismatch);
+        let mut best: Option<(usize, usize)> = None;
+        let mut ambiguous = false;
+
+        for (b, &idx) in &self.barcodes {
+            let Some(matches) = hamming(barcode, b, threshold) else {
+                continue;
+            };
+
+            match best {
+                Some((_, best_matches)) if matches < best_matches => (),
+                Some((_, best_matches)) if matches == best_matches => ambiguous = true,
+                _ => {
+                    best = Some((idx, matches));
+                    ambiguous = false;
+                }
+            }
+        }
+
+        if ambiguous {
+            None
+        } else {
+            best.map(|(idx, _)| idx)
+        }
+    }
+}
+
+impl<R: Reads, F: Fn(&[(&str, usize)]) + Send + Sync> Reads for DemuxReads<R, F> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+        let mut counts = self
+            .counts
+            .get_or(|| RefCell::new(vec![0; self.samples.len() + 1]))
+            .borrow_mut();
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "demultiplexing reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let barcode = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "demultiplexing reads",
+                })?;
+            let idx = self.assign(barcode).unwrap_or(self.samples.len());
+            counts[idx] += 1;
+
+            let sample = self
+                .samples
+                .get(idx)
+                .map(|s| s.as_str())
+                .unwrap_or(UNDETERMINED);
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(self.attr.str_type, self.attr.label, self.attr.attr)
+                .unwrap_or_else(|e| panic!("Error demultiplexing reads: {e}")) =
+                Data::Bytes(sample.as_bytes().to_owned());
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()?;
+
+        let mut counts = vec![0; self.samples.len() + 1];
+        for c in self.counts.iter_mut() {
+            for (a, b) in counts.iter_mut().zip(c.borrow().iter()) {
+                *a += b;
+            }
+        }
+
+        let res = self
+            .samples
+            .iter()
+            .map(|s| s.as_str())
+            .chain([UNDETERMINED])
+            .zip(counts)
+            .collect::<Vec<_>>();
+        (self.func)(&res);
+        Ok(())
+    }
+}
+
+const UNDETERMINED: &str = "undetermined";
+
+/// Read a CSV file with a barcode and a sample name on each line.
+///
+/// Empty lines and lines starting with `#` are skipped.
+fn read_sample_sheet(file: &str) -> Result<(BarcodeTable, Vec<String>)> {
+    let contents = std::fs::read_to_string(file).map_err(|e| Error::FileIo {
+        file: file.to_owned(),
+        source: Box::new(e),
+    })?;
+
+    let mut barcodes = FxHashMap::default();
+    let mut samples = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((barcode, sample)) = line.split_once(',') else {
+            return Err(Error::Parse {
+                string: line.to_owned(),
+                context: file.to_owned(),
+                reason: "expected barcode,sample",
+            });
+        };
+
+        let sample = sample.trim();
+
+        // the undetermined reads would be written to the same file as this sample
+        if sample == UNDETERMINED {
+            return Err(Error::Parse {
+                string: line.to_owned(),
+                context: file.to_owned(),
+                reason: "the sample name \"undetermined\" is reserved for undetermined reads",
+            });
+        }
+
+        if barcodes
+            .insert(barcode.trim().as_bytes().to_owned(), samples.len())
+            .is_some()
+        {
+            return Err(Error::Parse {
+                string: line.to_owned(),
+                context: file.to_owned(),
+                reason: "duplicate barcode",
+            });
+        }
+        samples.push(sample.to_owned());
+    }
+
+    Ok((barcodes, samples))
+}
@@ -0,0 +1,68 @@
+use crate::iter::*;
+
+pub struct DifferenceReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label1: Label,
+    label2: Label,
+    new_labels: [Option<Label>; 2],
+}
+
+impl<R: Reads> DifferenceReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(2, 2, "subtracting mappings in reads");
+        transform_expr.check_same_str_type("subtracting mappings in reads");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label1: transform_expr.before()[0].clone(),
+            label2: transform_expr.before()[1].clone(),
+            new_labels: [0, 1].map(|i| transform_expr.after()[i].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when subtracting mappings in reads"),
+            })),
+        }
+    }
+}
+
+impl<R: Reads> Reads for DifferenceReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "subtracting mappings in reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            read.difference(
+                self.label1.str_type,
+                self.label1.label,
+                self.label2.label,
+                self.new_labels[0].as_ref().map(|l| l.label),
+                self.new_labels[1].as_ref().map(|l| l.label),
+            )
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "subtracting mappings in reads",
+            })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
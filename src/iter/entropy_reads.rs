@@ -0,0 +1,108 @@
+use rustc_hash::FxHashMap;
+
+use crate::iter::*;
+
+pub struct EntropyReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    k: usize,
+}
+
+impl<R: Reads> EntropyReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        k: usize,
+    ) -> Self {
+        assert!(k >= 1, "k-mer length must be greater than zero");
+        transform_expr.check_size(1, 1, "computing the entropy of mappings");
+        transform_expr.check_same_str_type("computing the entropy of mappings");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when computing the entropy of mappings"),
+            }),
+            k,
+        }
+    }
+}
+
+impl<R: Reads> Reads for EntropyReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the entropy of mappings",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let entropy = entropy(
+                read.substring(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "computing the entropy of mappings",
+                    })?,
+                self.k,
+            );
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error computing the entropy of mappings: {e}")) =
+                Data::Float(entropy);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Shannon entropy (in bits) of the frequencies of the k-mers in a string.
+///
+/// Strings shorter than `k` have zero entropy.
+fn entropy(s: &[u8], k: usize) -> f64 {
+    if s.len() < k {
+        return 0.0;
+    }
+
+    let mut counts = FxHashMap::default();
+
+    for kmer in s.windows(k) {
+        *counts.entry(kmer).or_insert(0usize) += 1;
+    }
+
+    let total = (s.len() - k + 1) as f64;
+    counts
+        .values()
+        .map(|&c| {
+            let p = (c as f64) / total;
+            // written as a subtraction so a single k-mer results in 0.0 instead of -0.0
+            0.0 - p * p.log2()
+        })
+        .sum()
+}
@@ -0,0 +1,75 @@
+use crate::iter::*;
+
+pub struct ExpectedErrorsReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+}
+
+impl<R: Reads> ExpectedErrorsReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(1, 1, "computing the expected number of errors");
+        transform_expr.check_same_str_type("computing the expected number of errors");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when computing the expected number of errors"),
+            }),
+        }
+    }
+}
+
+impl<R: Reads> Reads for ExpectedErrorsReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the expected number of errors",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let (_, qual) = read
+                .substring_with_qual(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the expected number of errors",
+                })?;
+            let expected = qual
+                .iter()
+                .map(|&q| 10f64.powf(-(q.saturating_sub(b'!') as f64) / 10.0))
+                .sum::<f64>();
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error computing the expected number of errors: {e}")) =
+                Data::Float(expected);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
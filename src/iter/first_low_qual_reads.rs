@@ -0,0 +1,82 @@
+use crate::iter::*;
+
+pub struct FirstLowQualReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    threshold: u8,
+}
+
+impl<R: Reads> FirstLowQualReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        threshold: u8,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "finding the first low quality base");
+        transform_expr.check_same_str_type("finding the first low quality base");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when finding the first low quality base"),
+            }),
+            threshold,
+        }
+    }
+}
+
+impl<R: Reads> Reads for FirstLowQualReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "finding the first low quality base",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let (_, qual) = read
+                .substring_with_qual(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "finding the first low quality base",
+                })?;
+            let idx = qual
+                .iter()
+                .position(|&q| q.saturating_sub(b'!') < self.threshold)
+                .unwrap_or(qual.len());
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error finding the first low quality base: {e}")) =
+                Data::UInt(idx);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::iter::*;
+
+pub struct FlatMapReads<R: Reads, F: Fn(Read) -> Vec<Read> + Send + Sync> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    func: F,
+}
+
+impl<R: Reads, F: Fn(Read) -> Vec<Read> + Send + Sync> FlatMapReads<R, F> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, func: F) -> Self {
+        Self {
+            reads,
+            selector_expr,
+            func,
+        }
+    }
+}
+
+impl<R: Reads, F: Fn(Read) -> Vec<Read> + Send + Sync> Reads for FlatMapReads<R, F> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        loop {
+            let reads = self.reads.next_chunk()?;
+
+            if reads.is_empty() {
+                return Ok(reads);
+            }
+
+            let mut res = Vec::with_capacity(reads.len());
+
+            for read in reads.into_iter() {
+                if !(self
+                    .selector_expr
+                    .matches(&read)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "flat map",
+                    })?)
+                {
+                    res.push(read);
+                    continue;
+                }
+
+                res.extend((self.func)(read));
+            }
+
+            // an empty chunk means that there are no more reads, so only return once the
+            // function produces some reads
+            if !res.is_empty() {
+                return Ok(res);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
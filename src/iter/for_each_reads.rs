@@ -19,7 +19,7 @@ impl<R: Reads, F: Fn(&mut Read) + Send + Sync> ForEachReads<R, F> {
 impl<R: Reads, F: Fn(&mut Read) + Send + Sync> Reads for ForEachReads<R, F> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let mut reads = self.reads.next_chunk()?;
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -29,11 +29,13 @@ impl<R: Reads, F: Fn(&mut Read) + Send + Sync> Reads for ForEachReads<R, F> {
                     context: "for each",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             (self.func)(read);
-        }
+
+            Ok(())
+        })?;
         Ok(reads)
     }
 
@@ -0,0 +1,72 @@
+use crate::inline_string::InlineString;
+use crate::iter::*;
+
+pub struct GapsReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    str_type: StrType,
+    labels: Vec<InlineString>,
+    new_labels: Vec<Option<InlineString>>,
+}
+
+impl<R: Reads> GapsReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        let num_labels = transform_expr.before().len();
+        transform_expr.check_size(
+            num_labels,
+            num_labels + 1,
+            "finding gaps between mappings in reads",
+        );
+        transform_expr.check_same_str_type("finding gaps between mappings in reads");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            str_type: transform_expr.before()[0].str_type,
+            labels: transform_expr.before().iter().map(|l| l.label).collect(),
+            new_labels: transform_expr
+                .after()
+                .iter()
+                .map(|l| l.clone().map(|l| match l {
+                    LabelOrAttr::Label(l) => l.label,
+                    _ => panic!("Expected type.label after the \"->\" in the transform expression when finding gaps between mappings in reads"),
+                }))
+                .collect(),
+        }
+    }
+}
+
+impl<R: Reads> Reads for GapsReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "finding gaps between mappings in reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            read.gaps(self.str_type, &self.labels, &self.new_labels)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "finding gaps between mappings in reads",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,86 @@
+use crate::iter::*;
+
+pub struct GcContentReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    soft_mask: bool,
+}
+
+impl<R: Reads> GcContentReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(1, 1, "computing GC content");
+        transform_expr.check_same_str_type("computing GC content");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when computing GC content"),
+            }),
+            soft_mask: false,
+        }
+    }
+
+    /// Skip lowercase (soft-masked) bases when computing the GC content.
+    pub fn with_soft_mask(mut self) -> Self {
+        self.soft_mask = true;
+        self
+    }
+}
+
+impl<R: Reads> Reads for GcContentReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing GC content",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let string = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing GC content",
+                })?;
+            let (gc, total) = string
+                .iter()
+                .filter(|c| !(self.soft_mask && c.is_ascii_lowercase()))
+                .fold((0usize, 0usize), |(gc, total), c| {
+                    let is_gc = matches!(c, b'G' | b'C' | b'g' | b'c');
+                    (gc + (is_gc as usize), total + 1)
+                });
+            let frac = (gc as f64) / (total.max(1) as f64);
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error computing GC content: {e}")) = Data::Float(frac);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
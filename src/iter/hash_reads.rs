@@ -0,0 +1,89 @@
+use crate::iter::*;
+
+pub struct HashReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+}
+
+impl<R: Reads> HashReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(1, 1, "hashing mappings in reads");
+        transform_expr.check_same_str_type("hashing mappings in reads");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when hashing mappings in reads"),
+            }),
+        }
+    }
+}
+
+impl<R: Reads> Reads for HashReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "hashing mappings in reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            if let Some(attr) = &self.attr {
+                let hash = hash_bytes(
+                    read.substring(self.label.str_type, self.label.label)
+                        .map_err(|e| Error::NameError {
+                            source: e,
+                            read: read.clone(),
+                            context: "hashing mappings in reads",
+                        })?,
+                );
+
+                // panic to make borrow checker happy
+                *read
+                    .data_mut(attr.str_type, attr.label, attr.attr)
+                    .unwrap_or_else(|e| panic!("Error hashing mappings in reads: {e}")) =
+                    Data::UInt(hash as usize);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Fast, non-cryptographic 64-bit hash of some bytes.
+///
+/// This is FNV-1a followed by the splitmix64 finalizer to mix the bits.
+/// The result is stable across runs and platforms.
+pub(crate) fn hash_bytes(b: &[u8]) -> u64 {
+    let mut h = 0xcbf29ce484222325u64;
+
+    for &c in b {
+        h ^= c as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+
+    h ^= h >> 30;
+    h = h.wrapping_mul(0xbf58476d1ce4e5b9);
+    h ^= h >> 27;
+    h = h.wrapping_mul(0x94d049bb133111eb);
+    h ^ (h >> 31)
+}
@@ -15,7 +15,7 @@ impl<R: Reads> IntersectReads<R> {
 
         Self {
             reads,
-            selector_expr,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
             label1: transform_expr.before()[0].clone(),
             label2: transform_expr.before()[1].clone(),
             new_label: transform_expr.after()[0].clone().map(|l| match l {
@@ -30,7 +30,7 @@ impl<R: Reads> Reads for IntersectReads<R> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let mut reads = self.reads.next_chunk()?;
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -40,7 +40,7 @@ impl<R: Reads> Reads for IntersectReads<R> {
                     context: "intersecting mappings in reads",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             read.intersect(
@@ -54,7 +54,9 @@ impl<R: Reads> Reads for IntersectReads<R> {
                 read: read.clone(),
                 context: "intersecting mappings in reads",
             })?;
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -79,7 +81,7 @@ impl<R: Reads> UnionReads<R> {
 
         Self {
             reads,
-            selector_expr,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
             label1: transform_expr.before()[0].clone(),
             label2: transform_expr.before()[1].clone(),
             new_label: transform_expr.after()[0].clone().map(|l| match l {
@@ -94,7 +96,7 @@ impl<R: Reads> Reads for UnionReads<R> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let mut reads = self.reads.next_chunk()?;
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -104,7 +106,7 @@ impl<R: Reads> Reads for UnionReads<R> {
                     context: "unioning mappings in reads",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             read.union(
@@ -118,7 +120,9 @@ impl<R: Reads> Reads for UnionReads<R> {
                 read: read.clone(),
                 context: "unioning mappings in reads",
             })?;
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
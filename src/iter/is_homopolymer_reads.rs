@@ -0,0 +1,75 @@
+use crate::iter::*;
+
+pub struct IsHomopolymerReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+}
+
+impl<R: Reads> IsHomopolymerReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(1, 1, "checking for homopolymers");
+        transform_expr.check_same_str_type("checking for homopolymers");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when checking for homopolymers"),
+            }),
+        }
+    }
+}
+
+impl<R: Reads> Reads for IsHomopolymerReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "checking for homopolymers",
+                })?)
+            {
+                return Ok(());
+            }
+
+            if let Some(attr) = &self.attr {
+                let seq = read
+                    .substring(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "checking for homopolymers",
+                    })?;
+                let is_homopolymer = is_homopolymer(seq);
+
+                // panic to make borrow checker happy
+                *read
+                    .data_mut(attr.str_type, attr.label, attr.attr)
+                    .unwrap_or_else(|e| panic!("Error checking for homopolymers: {e}")) =
+                    Data::Bool(is_homopolymer);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Check whether all bases are the same, ignoring case. Empty strings are not homopolymers.
+pub fn is_homopolymer(seq: &[u8]) -> bool {
+    !seq.is_empty() && seq.iter().all(|c| c.eq_ignore_ascii_case(&seq[0]))
+}
@@ -22,7 +22,7 @@ impl<R: Reads, B: RangeBounds<usize> + Send + Sync> LengthInBoundsReads<R, B> {
 
         Self {
             reads,
-            selector_expr,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
             label: transform_expr.before()[0].clone(),
             attr: transform_expr.after()[0].clone().map(|a| match a {
                 LabelOrAttr::Attr(a) => a,
@@ -37,7 +37,7 @@ impl<R: Reads, B: RangeBounds<usize> + Send + Sync> Reads for LengthInBoundsRead
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let mut reads = self.reads.next_chunk()?;
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -47,7 +47,7 @@ impl<R: Reads, B: RangeBounds<usize> + Send + Sync> Reads for LengthInBoundsRead
                     context: "checking length in bounds",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             if let Some(attr) = &self.attr {
@@ -66,7 +66,9 @@ impl<R: Reads, B: RangeBounds<usize> + Send + Sync> Reads for LengthInBoundsRead
                     .unwrap_or_else(|e| panic!("Error checking length in bounds: {e}")) =
                     Data::Bool(self.bounds.contains(&len));
             }
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -0,0 +1,144 @@
+use rustc_hash::FxHashMap;
+use thread_local::*;
+
+use std::cell::RefCell;
+use std::fmt;
+
+use crate::iter::*;
+
+pub struct LengthSummaryReads<R: Reads, F: Fn(&LengthSummary) + Send + Sync> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    hists: ThreadLocal<RefCell<FxHashMap<usize, usize>>>,
+    func: F,
+}
+
+/// Summary statistics of the lengths of mappings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LengthSummary {
+    pub count: usize,
+    pub total: usize,
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+    pub median: usize,
+    pub n50: usize,
+}
+
+impl<R: Reads, F: Fn(&LengthSummary) + Send + Sync> LengthSummaryReads<R, F> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, label: Label, func: F) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require([label.clone().into()]),
+            label,
+            hists: ThreadLocal::new(),
+            func,
+        }
+    }
+}
+
+impl<R: Reads, F: Fn(&LengthSummary) + Send + Sync> Reads for LengthSummaryReads<R, F> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+        let mut hist = self
+            .hists
+            .get_or(|| RefCell::new(FxHashMap::default()))
+            .borrow_mut();
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "summarizing lengths",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let len = read
+                .mapping(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "summarizing lengths",
+                })?
+                .len;
+            *hist.entry(len).or_insert(0) += 1;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()?;
+
+        // merge histograms from all threads
+        let mut merged = FxHashMap::default();
+        for hist in self.hists.iter_mut() {
+            for (&len, &count) in hist.get_mut().iter() {
+                *merged.entry(len).or_insert(0) += count;
+            }
+        }
+
+        let mut hist = merged.into_iter().collect::<Vec<_>>();
+        hist.sort_unstable();
+        (self.func)(&LengthSummary::from_hist(&hist));
+        Ok(())
+    }
+}
+
+impl LengthSummary {
+    /// Compute the summary from a histogram of `(length, count)` pairs sorted by length.
+    fn from_hist(hist: &[(usize, usize)]) -> Self {
+        let count = hist.iter().map(|&(_, c)| c).sum::<usize>();
+        let total = hist.iter().map(|&(l, c)| l * c).sum::<usize>();
+
+        // the median is the lower middle length for an even number of lengths
+        let mut median = 0;
+        let mut seen = 0;
+        for &(l, c) in hist {
+            seen += c;
+            if seen * 2 >= count {
+                median = l;
+                break;
+            }
+        }
+
+        // N50 is the length where longer or equal lengths cover at least half of the bases
+        let mut n50 = 0;
+        let mut covered = 0;
+        for &(l, c) in hist.iter().rev() {
+            covered += l * c;
+            if covered * 2 >= total {
+                n50 = l;
+                break;
+            }
+        }
+
+        Self {
+            count,
+            total,
+            min: hist.first().map(|&(l, _)| l).unwrap_or(0),
+            max: hist.last().map(|&(l, _)| l).unwrap_or(0),
+            mean: (total as f64) / (count.max(1) as f64),
+            median,
+            n50,
+        }
+    }
+}
+
+impl fmt::Display for LengthSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "count: {}, total bases: {}, min: {}, max: {}, mean: {:.2}, median: {}, N50: {}",
+            self.count, self.total, self.min, self.max, self.mean, self.median, self.n50
+        )
+    }
+}
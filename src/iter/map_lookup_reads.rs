@@ -0,0 +1,86 @@
+use rustc_hash::FxHashMap;
+
+use crate::iter::*;
+
+pub struct MapLookupReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    map: FxHashMap<Vec<u8>, Vec<u8>>,
+    default: Option<Vec<u8>>,
+}
+
+impl<R: Reads> MapLookupReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        map: FxHashMap<Vec<u8>, Vec<u8>>,
+        default: Option<Vec<u8>>,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "looking up mappings in a map");
+        transform_expr.check_same_str_type("looking up mappings in a map");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when looking up mappings in a map"),
+            }),
+            map,
+            default,
+        }
+    }
+}
+
+impl<R: Reads> Reads for MapLookupReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "looking up mappings in a map",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let key = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "looking up mappings in a map",
+                })?;
+            let data = match self.map.get(key).or(self.default.as_ref()) {
+                Some(v) => Data::Bytes(v.clone()),
+                None => Data::Bool(false),
+            };
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error looking up mappings in a map: {e}")) = data;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,116 @@
+use crate::iter::hash_reads::hash_bytes;
+use crate::iter::*;
+
+pub struct MarkReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    attr: Attr,
+}
+
+impl<R: Reads> MarkReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, attr: Attr) -> Self {
+        Self {
+            reads,
+            selector_expr,
+            attr,
+        }
+    }
+}
+
+impl<R: Reads> Reads for MarkReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "marking reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let hash = hash_read(read, &self.attr);
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(self.attr.str_type, self.attr.label, self.attr.attr)
+                .unwrap_or_else(|e| panic!("Error marking reads: {e}")) = Data::UInt(hash);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+pub struct RetainChangedReads<R: Reads> {
+    reads: R,
+    attr: Attr,
+}
+
+impl<R: Reads> RetainChangedReads<R> {
+    pub fn new(reads: R, attr: Attr) -> Self {
+        Self { reads, attr }
+    }
+}
+
+impl<R: Reads> Reads for RetainChangedReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        loop {
+            let reads = self.reads.next_chunk()?;
+
+            if reads.is_empty() {
+                return Ok(reads);
+            }
+
+            let mut res = Vec::new();
+
+            for mut read in reads.into_iter() {
+                // unmarked reads are not retained since they cannot be compared
+                let Ok(prev_hash) = read
+                    .data(self.attr.str_type, self.attr.label, self.attr.attr)
+                    .and_then(|d| d.as_uint())
+                else {
+                    continue;
+                };
+
+                if hash_read(&mut read, &self.attr) != prev_hash {
+                    res.push(read);
+                }
+            }
+
+            // an empty chunk means that there are no more reads, so only return once some reads
+            // are kept
+            if !res.is_empty() {
+                return Ok(res);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Hash the whole read, with the mark attribute set to a placeholder so it does not affect the hash.
+fn hash_read(read: &mut Read, attr: &Attr) -> usize {
+    let data = read
+        .data_mut(attr.str_type, attr.label, attr.attr)
+        .unwrap_or_else(|e| panic!("Error marking reads: {e}"));
+    let prev = std::mem::replace(data, Data::UInt(0));
+    let hash = hash_bytes(&read.raw_bytes()) as usize;
+
+    *read
+        .data_mut(attr.str_type, attr.label, attr.attr)
+        .unwrap_or_else(|e| panic!("Error marking reads: {e}")) = prev;
+    hash
+}
@@ -0,0 +1,244 @@
+use crate::iter::*;
+
+pub struct MatchAnchorsReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    new_labels: [Option<Label>; 3],
+    prefix_patterns: Patterns,
+    suffix_patterns: Patterns,
+    identity: f64,
+    overlap: f64,
+}
+
+impl<R: Reads> MatchAnchorsReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        prefix_patterns: Patterns,
+        suffix_patterns: Patterns,
+        identity: f64,
+        overlap: f64,
+    ) -> Self {
+        let mut new_labels = [None, None, None];
+
+        transform_expr.check_size(1, 3, "matching anchors");
+        for (i, new_label) in new_labels.iter_mut().enumerate() {
+            *new_label = transform_expr.after()[i].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when matching anchors"),
+            });
+        }
+        transform_expr.check_same_str_type("matching anchors");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            new_labels,
+            prefix_patterns,
+            suffix_patterns,
+            identity,
+            overlap,
+        }
+    }
+}
+
+impl<R: Reads> Reads for MatchAnchorsReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+        let mut aligners = None;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "matching anchors",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let string = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "matching anchors",
+                })?;
+
+            let (prefix_aligner, suffix_aligner) = aligners.get_or_insert_with(|| {
+                (
+                    PrefixSuffixAligner::<true>::new(string.len() * 2),
+                    PrefixSuffixAligner::<false>::new(string.len() * 2),
+                )
+            });
+
+            let prefix_match = best_anchor(
+                read,
+                string,
+                &self.prefix_patterns,
+                prefix_aligner,
+                self.identity,
+                self.overlap,
+            )
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "matching anchors",
+            })?;
+            let suffix_match = best_anchor(
+                read,
+                string,
+                &self.suffix_patterns,
+                suffix_aligner,
+                self.identity,
+                self.overlap,
+            )
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "matching anchors",
+            })?;
+
+            // both anchors must match without overlapping each other
+            let anchors = match (prefix_match, suffix_match) {
+                (Some(p), Some(s)) if p.2 <= s.2 => Some((p, s)),
+                _ => None,
+            };
+
+            let mapping = read
+                .mapping_mut(self.label.str_type, self.label.label)
+                .unwrap();
+
+            let Some((
+                (prefix_str, prefix_attrs, prefix_end),
+                (suffix_str, suffix_attrs, suffix_start),
+            )) = anchors
+            else {
+                for patterns in [&self.prefix_patterns, &self.suffix_patterns] {
+                    if let Some(pattern_name) = patterns.pattern_name() {
+                        *mapping.data_mut(pattern_name) = Data::Bool(false);
+                    }
+                }
+                return Ok(());
+            };
+
+            for (patterns, pattern_str, pattern_attrs) in [
+                (&self.prefix_patterns, prefix_str, prefix_attrs),
+                (&self.suffix_patterns, suffix_str, suffix_attrs),
+            ] {
+                if let Some(pattern_name) = patterns.pattern_name() {
+                    *mapping.data_mut(pattern_name) = Data::Bytes(pattern_str);
+                }
+
+                for (&attr, data) in patterns.attr_names().iter().zip(pattern_attrs) {
+                    *mapping.data_mut(attr) = data.clone();
+                }
+            }
+
+            let offset = mapping.start;
+            let mapping_len = mapping.len;
+
+            let str_mappings = read.str_mappings_mut(self.label.str_type).unwrap();
+            // panic to make borrow checker happy
+            str_mappings
+                .add_mapping(
+                    self.new_labels[0].as_ref().map(|l| l.label),
+                    offset,
+                    prefix_end,
+                )
+                .unwrap_or_else(|e| panic!("Error matching anchors: {e}"));
+            str_mappings
+                .add_mapping(
+                    self.new_labels[1].as_ref().map(|l| l.label),
+                    offset + prefix_end,
+                    suffix_start - prefix_end,
+                )
+                .unwrap_or_else(|e| panic!("Error matching anchors: {e}"));
+            str_mappings
+                .add_mapping(
+                    self.new_labels[2].as_ref().map(|l| l.label),
+                    offset + suffix_start,
+                    mapping_len - suffix_start,
+                )
+                .unwrap_or_else(|e| panic!("Error matching anchors: {e}"));
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// A matched anchor: the pattern, its attributes, and the cut position.
+type Anchor<'a> = (Vec<u8>, &'a [Data], usize);
+
+/// Find the best matching pattern anchored at one end of the string.
+///
+/// Returns the pattern, its attributes, and the cut position: the end of the match for a prefix,
+/// or the start of the match for a suffix.
+fn best_anchor<'a, const PREFIX: bool>(
+    read: &Read,
+    string: &[u8],
+    patterns: &'a Patterns,
+    aligner: &mut PrefixSuffixAligner<PREFIX>,
+    identity: f64,
+    overlap: f64,
+) -> std::result::Result<Option<Anchor<'a>>, NameError> {
+    let mut max_matches = 0;
+    let mut max_pattern = None;
+
+    for pattern in patterns.patterns() {
+        let pattern_str = pattern.expr.format(read, false)?;
+        let pattern_len = pattern_str.len();
+
+        if max_matches >= pattern_len {
+            continue;
+        }
+
+        let additional = ((1.0 - identity).max(0.0) * (pattern_len as f64)).ceil() as usize;
+        let len = string.len().min(pattern_len + additional);
+
+        let matches = if PREFIX {
+            aligner
+                .align(&string[..len], &pattern_str, identity, overlap)
+                .map(|(m, _, end_idx)| (m, end_idx))
+        } else {
+            aligner
+                .align(
+                    &string[string.len() - len..],
+                    &pattern_str,
+                    identity,
+                    overlap,
+                )
+                .map(|(m, start_idx, _)| (m, string.len() - len + start_idx))
+        };
+
+        if let Some((matches, cut_pos)) = matches {
+            if matches > max_matches {
+                max_matches = matches;
+                // store the pattern's name instead of the pattern string if it has one
+                max_pattern = Some((
+                    pattern.name.clone().unwrap_or(pattern_str),
+                    pattern.attrs.as_slice(),
+                    cut_pos,
+                ));
+
+                if max_matches >= pattern_len {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(max_pattern)
+}
@@ -11,6 +11,13 @@ pub struct MatchAnyReads<R: Reads> {
     new_labels: [Option<Label>; 3],
     patterns: Patterns,
     match_type: MatchType,
+    soft_mask: bool,
+    min_overlap: usize,
+    identity_metric: IdentityMetric,
+    cigar_attr: Option<Attr>,
+    wildcards: bool,
+    wildcard_labels: Vec<Label>,
+    pattern_idx_attr: Option<Attr>,
 }
 
 impl<R: Reads> MatchAnyReads<R> {
@@ -34,13 +41,124 @@ impl<R: Reads> MatchAnyReads<R> {
 
         Self {
             reads,
-            selector_expr,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
             label: transform_expr.before()[0].clone(),
             new_labels,
             patterns,
             match_type,
+            soft_mask: false,
+            min_overlap: 0,
+            identity_metric: IdentityMetric::Columns,
+            cigar_attr: None,
+            wildcards: false,
+            wildcard_labels: Vec::new(),
+            pattern_idx_attr: None,
         }
     }
+
+    /// Treat lowercase (soft-masked) bases as unmatchable.
+    ///
+    /// This is only supported for exact and Hamming-distance-based matching, since the aligners
+    /// only accept nucleotide letters.
+    pub fn with_soft_mask(mut self) -> Self {
+        assert!(
+            !matches!(
+                self.match_type,
+                GlobalAln(_) | LocalAln { .. } | PrefixAln { .. } | SuffixAln { .. }
+            ),
+            "Soft masking is only supported for exact and Hamming-distance-based matching"
+        );
+        self.soft_mask = true;
+        self
+    }
+
+    /// Require at least `min_overlap` matching bases for prefix and suffix alignment.
+    ///
+    /// This applies to [`MatchType::PrefixAln`] and [`MatchType::SuffixAln`], and it is checked
+    /// in addition to the identity and overlap fraction thresholds. It is useful for rejecting
+    /// short spurious matches when the pattern is only partially present at the end of a string.
+    pub fn with_min_overlap(mut self, min_overlap: usize) -> Self {
+        self.min_overlap = min_overlap;
+        self
+    }
+
+    /// Set the denominator used for computing the sequence identity of alignments.
+    ///
+    /// This changes which matches pass the identity threshold of the alignment-based
+    /// [`MatchType`]s. The default is [`IdentityMetric::Columns`].
+    pub fn with_identity_metric(mut self, identity_metric: IdentityMetric) -> Self {
+        self.identity_metric = identity_metric;
+        self
+    }
+
+    /// Set the attribute of the input mapping that the matched pattern's name (or the pattern
+    /// string if it has no name) is stored into.
+    ///
+    /// This overrides the pattern name attribute of the patterns, like the one given to
+    /// [`Reads::match_any_fasta`].
+    pub fn with_pattern_name(mut self, pattern_name: impl AsRef<str>) -> Self {
+        self.patterns = self.patterns.with_pattern_name(pattern_name);
+        self
+    }
+
+    /// Store the CIGAR string of the alignment of the matched pattern into a bytes attribute.
+    ///
+    /// In the CIGAR string, `=` is a match, `X` is a mismatch, `I` is an insertion in the
+    /// pattern, and `D` is a deletion from the pattern. The attribute is only set for
+    /// alignment-based [`MatchType`]s, and it is set to false if no pattern matches.
+    pub fn with_cigar_attr(mut self, attr: Attr) -> Self {
+        self.cigar_attr = Some(attr);
+        self
+    }
+
+    /// Treat `N` in patterns as a wildcard that matches any single base.
+    ///
+    /// Wildcard positions do not count toward or against the threshold, so the threshold is
+    /// computed from the number of fixed (non-`N`) positions in the pattern and only fixed
+    /// positions are counted as matches.
+    ///
+    /// This is only supported for Hamming-distance-based [`MatchType`]s, since their matches
+    /// have no insertions or deletions.
+    pub fn with_wildcards(mut self) -> Self {
+        assert!(
+            matches!(
+                self.match_type,
+                Hamming(_) | HammingPrefix(_) | HammingSuffix(_) | HammingSearch(_)
+            ),
+            "Wildcards are only supported for Hamming-distance-based matching"
+        );
+        self.wildcards = true;
+        self
+    }
+
+    /// Create new mappings for the runs of wildcards in the matched pattern.
+    ///
+    /// The first run of consecutive `N`s in the matched pattern is labeled with the first label,
+    /// the second run with the second label, and so on. For example, with the pattern
+    /// `CAGNNNNNNGAC` and the label `seq1.umi`, the six variable bases become `seq1.umi`.
+    /// Labels for runs that are not in the matched pattern are not created.
+    ///
+    /// This also enables wildcards, like [`MatchAnyReads::with_wildcards`].
+    pub fn with_wildcard_labels(self, labels: impl Into<Vec<Label>>) -> Self {
+        let labels = labels.into();
+        assert!(
+            labels.iter().all(|l| l.str_type == self.label.str_type),
+            "Expected the wildcard labels to have the same type as the input mapping when matching patterns"
+        );
+
+        let mut res = self.with_wildcards();
+        res.wildcard_labels = labels;
+        res
+    }
+
+    /// Store the index of the matched pattern in the list of patterns into an unsigned integer
+    /// attribute.
+    ///
+    /// The attribute is set to false if no pattern matches.
+    pub fn with_pattern_index_attr(mut self, attr: Attr) -> Self {
+        self.pattern_idx_attr = Some(attr);
+        self
+    }
 }
 
 impl<R: Reads> Reads for MatchAnyReads<R> {
@@ -48,7 +166,7 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
         let mut reads = self.reads.next_chunk()?;
         let mut aligner: Option<Box<dyn Aligner>> = None;
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -58,7 +176,7 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                     context: "matching patterns",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             let string = read
@@ -68,24 +186,39 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                     read: read.clone(),
                     context: "matching patterns",
                 })?;
+            let masked;
+            let string = if self.soft_mask {
+                masked = mask_lowercase(string);
+                &masked
+            } else {
+                string
+            };
 
             if aligner.is_none() {
                 match self.match_type {
                     MatchType::GlobalAln(_) => {
-                        aligner =
-                            Some(Box::new(GlobalLocalAligner::<false>::new(string.len() * 2)));
+                        aligner = Some(Box::new(
+                            GlobalLocalAligner::<false>::new(string.len() * 2)
+                                .with_identity_metric(self.identity_metric),
+                        ));
                     }
                     MatchType::LocalAln { .. } => {
-                        aligner = Some(Box::new(GlobalLocalAligner::<true>::new(string.len() * 2)));
+                        aligner = Some(Box::new(
+                            GlobalLocalAligner::<true>::new(string.len() * 2)
+                                .with_identity_metric(self.identity_metric),
+                        ));
                     }
                     MatchType::PrefixAln { .. } => {
-                        aligner =
-                            Some(Box::new(PrefixSuffixAligner::<true>::new(string.len() * 2)));
+                        aligner = Some(Box::new(
+                            PrefixSuffixAligner::<true>::new(string.len() * 2)
+                                .with_identity_metric(self.identity_metric),
+                        ));
                     }
                     MatchType::SuffixAln { .. } => {
-                        aligner = Some(Box::new(PrefixSuffixAligner::<false>::new(
-                            string.len() * 2,
-                        )));
+                        aligner = Some(Box::new(
+                            PrefixSuffixAligner::<false>::new(string.len() * 2)
+                                .with_identity_metric(self.identity_metric),
+                        ));
                     }
                     _ => (),
                 }
@@ -95,8 +228,11 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
             let mut max_pattern = None;
             let mut max_cut_pos1 = 0;
             let mut max_cut_pos2 = 0;
+            let mut max_cigar = None;
+            let mut max_wildcard_runs = Vec::new();
+            let mut max_pattern_idx = None;
 
-            for pattern in self.patterns.patterns() {
+            for (pattern_idx, pattern) in self.patterns.patterns().iter().enumerate() {
                 let pattern_str =
                     pattern
                         .expr
@@ -107,8 +243,14 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                             context: "matching patterns",
                         })?;
                 let pattern_len = pattern_str.len();
+                // wildcard positions are excluded from the threshold
+                let fixed_len = if self.wildcards {
+                    pattern_str.iter().filter(|c| !is_wildcard(c)).count()
+                } else {
+                    pattern_len
+                };
 
-                if max_matches >= pattern_len {
+                if max_matches >= fixed_len {
                     continue;
                 }
 
@@ -140,13 +282,14 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                     ExactSearch => memmem::find(string, &pattern_str)
                         .map(|i| (pattern_len, i, i + pattern_len)),
                     Hamming(t) => {
-                        let t = t.get(pattern_len);
-                        hamming(string, &pattern_str, t).map(|m| (m, pattern_len, 0))
+                        let t = t.get(fixed_len);
+                        hamming_with(string, &pattern_str, t, self.wildcards)
+                            .map(|m| (m, pattern_len, 0))
                     }
                     HammingPrefix(t) => {
                         if pattern_len <= string.len() {
-                            let t = t.get(pattern_len);
-                            hamming(&string[..pattern_len], &pattern_str, t)
+                            let t = t.get(fixed_len);
+                            hamming_with(&string[..pattern_len], &pattern_str, t, self.wildcards)
                                 .map(|m| (m, pattern_len, 0))
                         } else {
                             None
@@ -154,16 +297,21 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                     }
                     HammingSuffix(t) => {
                         if pattern_len <= string.len() {
-                            let t = t.get(pattern_len);
-                            hamming(&string[string.len() - pattern_len..], &pattern_str, t)
-                                .map(|m| (m, string.len() - pattern_len, 0))
+                            let t = t.get(fixed_len);
+                            hamming_with(
+                                &string[string.len() - pattern_len..],
+                                &pattern_str,
+                                t,
+                                self.wildcards,
+                            )
+                            .map(|m| (m, string.len() - pattern_len, 0))
                         } else {
                             None
                         }
                     }
                     HammingSearch(t) => {
-                        let t = t.get(pattern_len);
-                        hamming_search(string, &pattern_str, t)
+                        let t = t.get(fixed_len);
+                        hamming_search(string, &pattern_str, t, self.wildcards)
                     }
                     GlobalAln(identity) => aligner
                         .as_mut()
@@ -184,6 +332,7 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                             .as_mut()
                             .unwrap()
                             .align(&string[..len], &pattern_str, identity, overlap)
+                            .filter(|&(m, _, _)| m >= self.min_overlap)
                             .map(|(m, _, end_idx)| (m, end_idx, 0))
                     }
                     SuffixAln { identity, overlap } => {
@@ -199,6 +348,7 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                                 identity,
                                 overlap,
                             )
+                            .filter(|&(m, _, _)| m >= self.min_overlap)
                             .map(|(m, start_idx, _)| (m, string.len() - len + start_idx, 0))
                     }
                 };
@@ -206,11 +356,29 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                 if let Some((matches, cut_pos1, cut_pos2)) = matches {
                     if matches > max_matches {
                         max_matches = matches;
-                        max_pattern = Some((pattern_str, &pattern.attrs));
                         max_cut_pos1 = cut_pos1;
                         max_cut_pos2 = cut_pos2;
+                        max_pattern_idx = Some(pattern_idx);
+                        if self.cigar_attr.is_some() {
+                            max_cigar = aligner.as_ref().map(|a| a.cigar());
+                        }
+                        if !self.wildcard_labels.is_empty() {
+                            // Hamming-based matches start at the first cut position, except for
+                            // prefix and whole string matches
+                            let match_start = match self.match_type {
+                                Hamming(_) | HammingPrefix(_) => 0,
+                                _ => cut_pos1,
+                            };
+                            max_wildcard_runs = wildcard_runs(&pattern_str)
+                                .into_iter()
+                                .map(|(start, len)| (match_start + start, len))
+                                .collect();
+                        }
+                        // store the pattern's name instead of the pattern string if it has one
+                        max_pattern =
+                            Some((pattern.name.clone().unwrap_or(pattern_str), &pattern.attrs));
 
-                        if max_matches >= pattern_len {
+                        if max_matches >= fixed_len {
                             break;
                         }
                     }
@@ -288,7 +456,45 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
                     *mapping.data_mut(pattern_name) = Data::Bool(false);
                 }
             }
-        }
+
+            if !max_wildcard_runs.is_empty() {
+                let offset = read
+                    .mapping(self.label.str_type, self.label.label)
+                    .unwrap()
+                    .start;
+                let str_mappings = read.str_mappings_mut(self.label.str_type).unwrap();
+
+                self.wildcard_labels
+                    .iter()
+                    .zip(max_wildcard_runs)
+                    .try_for_each(|(label, (start, len))| {
+                        str_mappings.add_mapping(Some(label.label), offset + start, len)
+                    })
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "matching patterns",
+                    })?;
+            }
+
+            if let Some(attr) = &self.pattern_idx_attr {
+                // panic to make borrow checker happy
+                *read
+                    .data_mut(attr.str_type, attr.label, attr.attr)
+                    .unwrap_or_else(|e| panic!("Error matching patterns: {e}")) =
+                    max_pattern_idx.map(Data::UInt).unwrap_or(Data::Bool(false));
+            }
+
+            if let (Some(attr), true) = (&self.cigar_attr, aligner.is_some()) {
+                // panic to make borrow checker happy
+                *read
+                    .data_mut(attr.str_type, attr.label, attr.attr)
+                    .unwrap_or_else(|e| panic!("Error matching patterns: {e}")) =
+                    max_cigar.map(Data::Bytes).unwrap_or(Data::Bool(false));
+            }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -298,7 +504,14 @@ impl<R: Reads> Reads for MatchAnyReads<R> {
     }
 }
 
-fn hamming(a: &[u8], b: &[u8], threshold: usize) -> Option<usize> {
+/// Replace lowercase bases with a byte that never matches any pattern.
+fn mask_lowercase(s: &[u8]) -> Vec<u8> {
+    s.iter()
+        .map(|&c| if c.is_ascii_lowercase() { 0 } else { c })
+        .collect()
+}
+
+pub(crate) fn hamming(a: &[u8], b: &[u8], threshold: usize) -> Option<usize> {
     if a.len() != b.len() {
         return None;
     }
@@ -339,11 +552,62 @@ fn hamming(a: &[u8], b: &[u8], threshold: usize) -> Option<usize> {
     }
 }
 
-fn hamming_search(a: &[u8], b: &[u8], threshold: usize) -> Option<(usize, usize, usize)> {
+/// Count matching bases for Hamming-distance-based matching, optionally treating `N` in `b` as
+/// a wildcard. Wildcard positions are not counted as matches.
+fn hamming_with(a: &[u8], b: &[u8], threshold: usize, wildcards: bool) -> Option<usize> {
+    if !wildcards {
+        return hamming(a, b, threshold);
+    }
+
+    if a.len() != b.len() {
+        return None;
+    }
+
+    let matches = a
+        .iter()
+        .zip(b)
+        .filter(|&(x, y)| !is_wildcard(y) && x == y)
+        .count();
+
+    if matches >= threshold {
+        Some(matches)
+    } else {
+        None
+    }
+}
+
+fn is_wildcard(c: &u8) -> bool {
+    c.eq_ignore_ascii_case(&b'N')
+}
+
+/// Find the start and length of each run of consecutive wildcards.
+fn wildcard_runs(pattern: &[u8]) -> Vec<(usize, usize)> {
+    let mut res: Vec<(usize, usize)> = Vec::new();
+
+    for (i, c) in pattern.iter().enumerate() {
+        if !is_wildcard(c) {
+            continue;
+        }
+
+        match res.last_mut() {
+            Some((start, len)) if *start + *len == i => *len += 1,
+            _ => res.push((i, 1)),
+        }
+    }
+
+    res
+}
+
+fn hamming_search(
+    a: &[u8],
+    b: &[u8],
+    threshold: usize,
+    wildcards: bool,
+) -> Option<(usize, usize, usize)> {
     let mut best_match = None;
 
     for (i, w) in a.windows(b.len()).enumerate() {
-        if let Some(matches) = hamming(w, b, threshold) {
+        if let Some(matches) = hamming_with(w, b, threshold, wildcards) {
             if let Some((best_matches, _, _)) = best_match {
                 if matches <= best_matches {
                     continue;
@@ -357,7 +621,7 @@ fn hamming_search(a: &[u8], b: &[u8], threshold: usize) -> Option<(usize, usize,
     best_match
 }
 
-trait Aligner {
+pub(crate) trait Aligner {
     fn align(
         &mut self,
         read: &[u8],
@@ -365,6 +629,35 @@ trait Aligner {
         identity_threshold: f64,
         overlap_threshold: f64,
     ) -> Option<(usize, usize, usize)>;
+
+    /// CIGAR string of the last alignment, with the pattern as the query.
+    fn cigar(&self) -> Vec<u8>;
+}
+
+fn cigar_string(cigar: &Cigar, reverse: bool, swap_indels: bool) -> Vec<u8> {
+    let mut ops = (0..cigar.len()).map(|i| cigar.get(i)).collect::<Vec<_>>();
+
+    if reverse {
+        ops.reverse();
+    }
+
+    let mut res = Vec::new();
+
+    for OpLen { op, len } in ops {
+        let c = match op {
+            Operation::Eq => b'=',
+            Operation::X => b'X',
+            Operation::I if swap_indels => b'D',
+            Operation::D if swap_indels => b'I',
+            Operation::I => b'I',
+            Operation::D => b'D',
+            _ => b'M',
+        };
+        res.extend_from_slice(len.to_string().as_bytes());
+        res.push(c);
+    }
+
+    res
 }
 
 struct GlobalLocalAligner<const LOCAL: bool> {
@@ -375,6 +668,7 @@ struct GlobalLocalAligner<const LOCAL: bool> {
     block: Block<true, LOCAL, LOCAL, false>,
     cigar: Cigar,
     len: usize,
+    identity_metric: IdentityMetric,
 }
 
 impl<const LOCAL: bool> GlobalLocalAligner<LOCAL> {
@@ -400,9 +694,15 @@ impl<const LOCAL: bool> GlobalLocalAligner<LOCAL> {
             block,
             cigar,
             len,
+            identity_metric: IdentityMetric::Columns,
         }
     }
 
+    pub fn with_identity_metric(mut self, identity_metric: IdentityMetric) -> Self {
+        self.identity_metric = identity_metric;
+        self
+    }
+
     fn resize_if_needed(&mut self, len: usize) {
         if len > self.len {
             self.read_padded = PaddedBytes::new::<NucMatrix>(len, Self::MAX_SIZE);
@@ -480,7 +780,9 @@ impl<const LOCAL: bool> Aligner for GlobalLocalAligner<LOCAL> {
             total += len;
         }
 
-        let identity = (matches as f64) / (total as f64);
+        let identity = self
+            .identity_metric
+            .identity(matches, total, pattern.len(), read.len());
         let overlap = (matches as f64) / (pattern.len() as f64);
 
         if identity >= identity_threshold && overlap >= overlap_threshold {
@@ -489,9 +791,13 @@ impl<const LOCAL: bool> Aligner for GlobalLocalAligner<LOCAL> {
             None
         }
     }
+
+    fn cigar(&self) -> Vec<u8> {
+        cigar_string(&self.cigar, false, false)
+    }
 }
 
-struct PrefixSuffixAligner<const PREFIX: bool> {
+pub(crate) struct PrefixSuffixAligner<const PREFIX: bool> {
     read_padded: PaddedBytes,
     pattern_padded: PaddedBytes,
     matrix: NucMatrix,
@@ -500,6 +806,9 @@ struct PrefixSuffixAligner<const PREFIX: bool> {
     block2: Block<true, false, false, true>, // no X-drop
     cigar: Cigar,
     len: usize,
+    identity_metric: IdentityMetric,
+    // whether the last alignment used the second block with read and pattern switched
+    switched: bool,
 }
 
 impl<const PREFIX: bool> PrefixSuffixAligner<PREFIX> {
@@ -526,9 +835,16 @@ impl<const PREFIX: bool> PrefixSuffixAligner<PREFIX> {
             block2,
             cigar,
             len,
+            identity_metric: IdentityMetric::Columns,
+            switched: false,
         }
     }
 
+    pub fn with_identity_metric(mut self, identity_metric: IdentityMetric) -> Self {
+        self.identity_metric = identity_metric;
+        self
+    }
+
     fn resize_if_needed(&mut self, len: usize) {
         if len > self.len {
             self.read_padded = PaddedBytes::new::<NucMatrix>(len, Self::MAX_SIZE);
@@ -600,7 +916,8 @@ impl<const PREFIX: bool> Aligner for PrefixSuffixAligner<PREFIX> {
         }
 
         // skip second alignment if first alignment reaches the end of the read
-        if res.reference_idx < read.len() {
+        self.switched = res.reference_idx < read.len();
+        if self.switched {
             // get the overlapping prefix/suffix region
             if PREFIX {
                 self.read_padded
@@ -647,7 +964,9 @@ impl<const PREFIX: bool> Aligner for PrefixSuffixAligner<PREFIX> {
             total += len;
         }
 
-        let identity = (matches as f64) / (total as f64);
+        let identity = self
+            .identity_metric
+            .identity(matches, total, pattern.len(), read.len());
         let overlap = (matches as f64) / (pattern.len() as f64);
 
         if identity >= identity_threshold && overlap >= overlap_threshold {
@@ -663,4 +982,12 @@ impl<const PREFIX: bool> Aligner for PrefixSuffixAligner<PREFIX> {
             None
         }
     }
+
+    fn cigar(&self) -> Vec<u8> {
+        // traceback starts from the end of the sequences, so the operations only need to be
+        // reversed if the sequences were not reversed before aligning: the sequences are
+        // reversed for prefix alignment in the first alignment and for suffix alignment in the
+        // second alignment, which also has the read and pattern switched
+        cigar_string(&self.cigar, PREFIX == self.switched, self.switched)
+    }
 }
@@ -25,7 +25,7 @@ impl<R: Reads> MatchPolyXReads<R> {
 
         Self {
             reads,
-            selector_expr,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
             label: transform_expr.before()[0].clone(),
             new_label1: transform_expr.after()[0].clone().map(|l| match l {
                 LabelOrAttr::Label(l) => l,
@@ -46,7 +46,7 @@ impl<R: Reads> Reads for MatchPolyXReads<R> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let mut reads = self.reads.next_chunk()?;
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -56,7 +56,7 @@ impl<R: Reads> Reads for MatchPolyXReads<R> {
                     context: "matching poly(X)",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             let string = read
@@ -81,7 +81,9 @@ impl<R: Reads> Reads for MatchPolyXReads<R> {
                     context: "matching poly(X)",
                 })?;
             }
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -26,7 +26,7 @@ impl<R: Reads> MatchRegexReads<R> {
 
         Self {
             reads,
-            selector_expr,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
             label: transform_expr.before()[0].clone(),
             attr: transform_expr.after()[0].clone().map(|a| match a {
                 LabelOrAttr::Attr(a) => a,
@@ -48,7 +48,7 @@ impl<R: Reads> Reads for MatchRegexReads<R> {
             .collect::<Vec<_>>();
         let mut new_mappings = Vec::new();
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -58,7 +58,7 @@ impl<R: Reads> Reads for MatchRegexReads<R> {
                     context: "matching regex",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             let string = read
@@ -97,7 +97,9 @@ impl<R: Reads> Reads for MatchRegexReads<R> {
                     .data_mut(attr.str_type, attr.label, attr.attr)
                     .unwrap_or_else(|e| panic!("Error matching regex: {e}")) = Data::Bool(matched);
             }
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -0,0 +1,92 @@
+use crate::iter::*;
+
+pub struct MateGuidedCutReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    cut_label: Label,
+    new_label1: Option<Label>,
+    new_label2: Option<Label>,
+    source: LabelOrAttr,
+}
+
+impl<R: Reads> MateGuidedCutReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        source: LabelOrAttr,
+    ) -> Self {
+        transform_expr.check_size(1, 2, "cutting reads guided by the mate");
+        transform_expr.check_same_str_type("cutting reads guided by the mate");
+
+        Self {
+            reads,
+            selector_expr: selector_expr
+                .require(transform_expr.required_names())
+                .require([source.clone()]),
+            cut_label: transform_expr.before()[0].clone(),
+            new_label1: transform_expr.after()[0].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when cutting reads guided by the mate"),
+            }),
+            new_label2: transform_expr.after()[1].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when cutting reads guided by the mate"),
+            }),
+            source,
+        }
+    }
+}
+
+impl<R: Reads> Reads for MateGuidedCutReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "cutting reads guided by the mate",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let cut_idx = match &self.source {
+                LabelOrAttr::Label(l) => read.mapping(l.str_type, l.label).map(|m| m.len),
+                LabelOrAttr::Attr(a) => read
+                    .data(a.str_type, a.label, a.attr)
+                    .and_then(|d| d.as_uint()),
+            }
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "cutting reads guided by the mate",
+            })?;
+
+            read.cut(
+                self.cut_label.str_type,
+                self.cut_label.label,
+                self.new_label1.as_ref().map(|l| l.label),
+                self.new_label2.as_ref().map(|l| l.label),
+                LeftEnd(cut_idx),
+            )
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "cutting reads guided by the mate",
+            })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::iter::*;
+
+pub struct MaxBasesReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    max_bases: usize,
+    bases: AtomicUsize,
+}
+
+impl<R: Reads> MaxBasesReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, label: Label, max_bases: usize) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require([label.clone().into()]),
+            label,
+            max_bases,
+            bases: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<R: Reads> Reads for MaxBasesReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        // stop early without reading more input once enough bases are seen
+        if self.bases.load(Ordering::Relaxed) >= self.max_bases {
+            return Ok(Vec::new());
+        }
+
+        let mut reads = self.reads.next_chunk()?;
+
+        try_retain_reads(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "limiting the number of bases",
+                })?)
+            {
+                return Ok(true);
+            }
+
+            let len = read
+                .mapping(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "limiting the number of bases",
+                })?
+                .len;
+
+            // keep the read that reaches the limit
+            Ok(self.bases.fetch_add(len, Ordering::Relaxed) < self.max_bases)
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,74 @@
+use crate::iter::*;
+
+pub struct MaxNFracReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    max_frac: f64,
+}
+
+impl<R: Reads> MaxNFracReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, label: Label, max_frac: f64) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require([LabelOrAttr::Label(label.clone())]),
+            label,
+            max_frac,
+        }
+    }
+}
+
+impl<R: Reads> Reads for MaxNFracReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        loop {
+            let mut reads = self.reads.next_chunk()?;
+
+            if reads.is_empty() {
+                return Ok(reads);
+            }
+
+            try_retain_reads(&mut reads, |read| {
+                if !(self
+                    .selector_expr
+                    .matches(read)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "filtering reads by N content",
+                    })?)
+                {
+                    return Ok(true);
+                }
+
+                let string = read
+                    .substring(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "filtering reads by N content",
+                    })?;
+                let n = string
+                    .iter()
+                    .filter(|c| c.eq_ignore_ascii_case(&b'N'))
+                    .count();
+                let frac = if string.is_empty() {
+                    0.0
+                } else {
+                    (n as f64) / (string.len() as f64)
+                };
+
+                Ok(frac <= self.max_frac)
+            })?;
+
+            // an empty chunk means that there are no more reads, so only return once some reads
+            // are kept
+            if !reads.is_empty() {
+                return Ok(reads);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,178 @@
+use crate::iter::*;
+
+pub struct MeanQualReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    float: bool,
+    require_qual: bool,
+}
+
+impl<R: Reads> MeanQualReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(1, 1, "computing mean quality scores");
+        transform_expr.check_same_str_type("computing mean quality scores");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when computing mean quality scores"),
+            }),
+            float: false,
+            require_qual: false,
+        }
+    }
+
+    /// Store the mean as a float attribute without rounding, instead of an unsigned integer.
+    pub fn with_float(mut self) -> Self {
+        self.float = true;
+        self
+    }
+
+    /// Return an error for mappings without quality scores, instead of skipping the read.
+    pub fn with_require_qual(mut self) -> Self {
+        self.require_qual = true;
+        self
+    }
+}
+
+impl<R: Reads> Reads for MeanQualReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing mean quality scores",
+                })?)
+            {
+                return Ok(());
+            }
+
+            if let Some(attr) = &self.attr {
+                let qual = read
+                    .substring_qual(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "computing mean quality scores",
+                    })?;
+
+                // strings without quality scores are skipped unless quality scores are required
+                let Some(qual) = qual else {
+                    if self.require_qual {
+                        return Err(Error::NameError {
+                            source: NameError::NoQual(Name::StrType(self.label.str_type)),
+                            read: read.clone(),
+                            context: "computing mean quality scores",
+                        });
+                    }
+
+                    return Ok(());
+                };
+
+                let mean = mean_qual_score(qual);
+                let data = if self.float {
+                    Data::Float(mean)
+                } else {
+                    Data::UInt(mean.round() as usize)
+                };
+
+                // panic to make borrow checker happy
+                *read
+                    .data_mut(attr.str_type, attr.label, attr.attr)
+                    .unwrap_or_else(|e| panic!("Error computing mean quality scores: {e}")) = data;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Mean of Phred+33 quality scores. Empty quality scores result in zero.
+pub(crate) fn mean_qual_score(qual: &[u8]) -> f64 {
+    let sum = qual
+        .iter()
+        .map(|&q| q.saturating_sub(b'!') as usize)
+        .sum::<usize>();
+    sum as f64 / qual.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::*;
+    use crate::inline_string::*;
+    use crate::*;
+
+    // quality scores 0, 0, 0, and 10
+    const FASTQ: &[u8] = b"@read\nACGT\n+\n!!!+\n";
+
+    fn mean(read: &Read, str_type: StrType) -> Option<Data> {
+        read.data(
+            str_type,
+            InlineString::new(b"*"),
+            InlineString::new(b"mean"),
+        )
+        .ok()
+        .cloned()
+    }
+
+    #[test]
+    fn test_mean_qual() {
+        let reads = iter_fastq1_bytes(FASTQ)
+            .unwrap()
+            .mean_qual(sel!(), tr!(seq1.* -> seq1.*.mean))
+            .run_collect_reads()
+            .unwrap();
+        assert_eq!(mean(&reads[0], StrType::Seq1), Some(Data::UInt(3)));
+    }
+
+    #[test]
+    fn test_mean_qual_float() {
+        let reads = iter_fastq1_bytes(FASTQ)
+            .unwrap()
+            .mean_qual(sel!(), tr!(seq1.* -> seq1.*.mean))
+            .with_float()
+            .run_collect_reads()
+            .unwrap();
+        assert_eq!(mean(&reads[0], StrType::Seq1), Some(Data::Float(2.5)));
+    }
+
+    #[test]
+    fn test_mean_qual_require_qual() {
+        // names do not have quality scores, so they are skipped by default
+        let reads = iter_fastq1_bytes(FASTQ)
+            .unwrap()
+            .mean_qual(sel!(), tr!(name1.* -> name1.*.mean))
+            .run_collect_reads()
+            .unwrap();
+        assert_eq!(mean(&reads[0], StrType::Name1), None);
+
+        let res = iter_fastq1_bytes(FASTQ)
+            .unwrap()
+            .mean_qual(sel!(), tr!(name1.* -> name1.*.mean))
+            .with_require_qual()
+            .run_collect_reads();
+        assert!(matches!(
+            res,
+            Err(Error::NameError {
+                source: NameError::NoQual(_),
+                ..
+            })
+        ));
+    }
+}
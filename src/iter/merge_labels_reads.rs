@@ -0,0 +1,78 @@
+use crate::inline_string::InlineString;
+use crate::iter::*;
+
+pub struct MergeLabelsReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    str_type: StrType,
+    labels: Vec<InlineString>,
+    new_label: Option<InlineString>,
+}
+
+impl<R: Reads> MergeLabelsReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        let num_labels = transform_expr.before().len();
+        assert!(
+            num_labels >= 1,
+            "Expected at least one input mapping when merging mappings in reads"
+        );
+        transform_expr.check_size(num_labels, 1, "merging mappings in reads");
+        transform_expr.check_same_str_type("merging mappings in reads");
+
+        let labels = transform_expr
+            .before()
+            .iter()
+            .map(|l| l.label)
+            .collect::<Vec<_>>();
+        assert!(
+            !labels.contains(&InlineString::new(b"*")),
+            "Cannot merge the \"*\" mapping when merging mappings in reads"
+        );
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            str_type: transform_expr.before()[0].str_type,
+            labels,
+            new_label: transform_expr.after()[0].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l.label,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when merging mappings in reads"),
+            }),
+        }
+    }
+}
+
+impl<R: Reads> Reads for MergeLabelsReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "merging mappings in reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            read.merge(self.str_type, &self.labels, self.new_label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "merging mappings in reads",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
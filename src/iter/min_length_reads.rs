@@ -0,0 +1,84 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::iter::*;
+
+pub struct MinLengthReads<R: Reads, F: Fn(usize) + Send + Sync> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    min_len: usize,
+    dropped: AtomicUsize,
+    func: F,
+}
+
+impl<R: Reads, F: Fn(usize) + Send + Sync> MinLengthReads<R, F> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        label: Label,
+        min_len: usize,
+        func: F,
+    ) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require([LabelOrAttr::Label(label.clone())]),
+            label,
+            min_len,
+            dropped: AtomicUsize::new(0),
+            func,
+        }
+    }
+}
+
+impl<R: Reads, F: Fn(usize) + Send + Sync> Reads for MinLengthReads<R, F> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        loop {
+            let mut reads = self.reads.next_chunk()?;
+
+            if reads.is_empty() {
+                return Ok(reads);
+            }
+
+            try_retain_reads(&mut reads, |read| {
+                if !(self
+                    .selector_expr
+                    .matches(read)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "filtering reads by minimum length",
+                    })?)
+                {
+                    return Ok(true);
+                }
+
+                let len = read
+                    .mapping(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "filtering reads by minimum length",
+                    })?
+                    .len;
+
+                if len < self.min_len {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+
+                Ok(len >= self.min_len)
+            })?;
+
+            // an empty chunk means that there are no more reads, so only return once some reads
+            // are kept
+            if !reads.is_empty() {
+                return Ok(reads);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()?;
+        (self.func)(self.dropped.load(Ordering::Relaxed));
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+use crate::iter::*;
+
+pub struct NumericReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    attr: Attr,
+    new_attr: Attr,
+    op: NumericOp,
+}
+
+impl<R: Reads> NumericReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        attr: Attr,
+        new_attr: Attr,
+        op: NumericOp,
+    ) -> Self {
+        let required = std::iter::once(&attr)
+            .chain(op.operands().filter_map(|o| o.attr()))
+            .cloned()
+            .map(LabelOrAttr::Attr)
+            .collect::<Vec<_>>();
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(required),
+            attr,
+            new_attr,
+            op,
+        }
+    }
+}
+
+impl<R: Reads> Reads for NumericReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing a numeric attribute",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let res = read
+                .data(self.attr.str_type, self.attr.label, self.attr.attr)
+                .and_then(|x| self.op.eval(x, read))
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing a numeric attribute",
+                })?;
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(
+                    self.new_attr.str_type,
+                    self.new_attr.label,
+                    self.new_attr.attr,
+                )
+                .unwrap_or_else(|e| panic!("Error computing a numeric attribute: {e}")) = res;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+impl NumericOp {
+    fn operands(&self) -> impl Iterator<Item = &Operand> {
+        use NumericOp::*;
+        match self {
+            Rem(o) | Min(o) | Max(o) => vec![o].into_iter(),
+            Clamp(lo, hi) => vec![lo, hi].into_iter(),
+            Floor | Ceil | Round => Vec::new().into_iter(),
+        }
+    }
+
+    fn eval(&self, x: &Data, read: &Read) -> std::result::Result<Data, NameError> {
+        use NumericOp::*;
+        match self {
+            Rem(o) => match (x, &o.eval(read)?) {
+                (Data::UInt(_), Data::UInt(0)) => Err(NameError::DivisionByZero),
+                (Data::UInt(a), Data::UInt(b)) => Ok(Data::UInt(a % b)),
+                (Data::Float(_), Data::Float(b)) if *b == 0.0 => Err(NameError::DivisionByZero),
+                (Data::Float(a), Data::Float(b)) => Ok(Data::Float(a % b)),
+                (a, b) => Err(type_error(a, b)),
+            },
+            Min(o) => binary(x, &o.eval(read)?, |a, b| a.min(b), |a, b| a.min(b)),
+            Max(o) => binary(x, &o.eval(read)?, |a, b| a.max(b), |a, b| a.max(b)),
+            Clamp(lo, hi) => {
+                let lo = lo.eval(read)?;
+                let hi = hi.eval(read)?;
+                let valid = match (&lo, &hi) {
+                    (Data::UInt(a), Data::UInt(b)) => a <= b,
+                    (Data::Float(a), Data::Float(b)) => a <= b,
+                    (a, b) => return Err(type_error(a, b)),
+                };
+
+                if !valid {
+                    return Err(NameError::InvalidBounds(lo, hi));
+                }
+
+                let res = binary(x, &lo, |a, b| a.max(b), |a, b| a.max(b))?;
+                binary(&res, &hi, |a, b| a.min(b), |a, b| a.min(b))
+            }
+            Floor => to_uint(x, f64::floor),
+            Ceil => to_uint(x, f64::ceil),
+            Round => to_uint(x, f64::round_ties_even),
+        }
+    }
+}
+
+fn binary(
+    a: &Data,
+    b: &Data,
+    uint_op: impl Fn(usize, usize) -> usize,
+    float_op: impl Fn(f64, f64) -> f64,
+) -> std::result::Result<Data, NameError> {
+    match (a, b) {
+        (Data::UInt(a), Data::UInt(b)) => Ok(Data::UInt(uint_op(*a, *b))),
+        (Data::Float(a), Data::Float(b)) => Ok(Data::Float(float_op(*a, *b))),
+        (a, b) => Err(type_error(a, b)),
+    }
+}
+
+fn to_uint(x: &Data, op: impl Fn(f64) -> f64) -> std::result::Result<Data, NameError> {
+    let Data::Float(x) = x else {
+        return Err(NameError::Type("float", x.clone()));
+    };
+    let res = op(*x);
+
+    // negative zero is allowed, and usize::MAX rounds up to 2^64 as a float, which is already out
+    // of range
+    if !res.is_finite() || res < 0.0 || res >= usize::MAX as f64 {
+        return Err(NameError::Type(
+            "finite non-negative float within the uint range",
+            Data::Float(*x),
+        ));
+    }
+
+    Ok(Data::UInt(res as usize))
+}
+
+/// Error for operands that are not both unsigned integers or both floats.
+fn type_error(a: &Data, b: &Data) -> NameError {
+    match a {
+        Data::UInt(_) => NameError::Type("uint", b.clone()),
+        Data::Float(_) => NameError::Type("float", b.clone()),
+        _ => NameError::Type("uint or float", a.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fastq::Origin;
+
+    fn eval(x: Data, op: NumericOp) -> std::result::Result<Data, NameError> {
+        let read = Read::from_fastq1(b"read", b"ACGT", b"IIII", Arc::new(Origin::Bytes), 0);
+        op.eval(&x, &read)
+    }
+
+    #[test]
+    fn test_min_max() {
+        assert_eq!(
+            eval(Data::UInt(3), NumericOp::Max(7.into())).unwrap(),
+            Data::UInt(7)
+        );
+        assert_eq!(
+            eval(Data::UInt(3), NumericOp::Min(7.into())).unwrap(),
+            Data::UInt(3)
+        );
+        assert_eq!(
+            eval(Data::Float(3.5), NumericOp::Max(1.5.into())).unwrap(),
+            Data::Float(3.5)
+        );
+    }
+
+    #[test]
+    fn test_min_max_mixed_types() {
+        assert!(matches!(
+            eval(Data::Float(3.0), NumericOp::Max(7.into())),
+            Err(NameError::Type("float", Data::UInt(7)))
+        ));
+        assert!(matches!(
+            eval(Data::UInt(3), NumericOp::Min(7.0.into())),
+            Err(NameError::Type("uint", Data::Float(_)))
+        ));
+    }
+
+    #[test]
+    fn test_to_uint() {
+        assert_eq!(
+            eval(Data::Float(2.5), NumericOp::Round).unwrap(),
+            Data::UInt(2)
+        );
+        assert_eq!(
+            eval(Data::Float(3.5), NumericOp::Round).unwrap(),
+            Data::UInt(4)
+        );
+        assert_eq!(
+            eval(Data::Float(2.5), NumericOp::Floor).unwrap(),
+            Data::UInt(2)
+        );
+        assert_eq!(
+            eval(Data::Float(2.5), NumericOp::Ceil).unwrap(),
+            Data::UInt(3)
+        );
+    }
+
+    #[test]
+    fn test_to_uint_negative() {
+        // values that round to zero are allowed
+        assert_eq!(
+            eval(Data::Float(-0.5), NumericOp::Round).unwrap(),
+            Data::UInt(0)
+        );
+        assert_eq!(
+            eval(Data::Float(-0.5), NumericOp::Ceil).unwrap(),
+            Data::UInt(0)
+        );
+
+        assert!(matches!(
+            eval(Data::Float(-0.5), NumericOp::Floor),
+            Err(NameError::Type(_, Data::Float(_)))
+        ));
+        assert!(matches!(
+            eval(Data::Float(-2.5), NumericOp::Round),
+            Err(NameError::Type(_, Data::Float(_)))
+        ));
+    }
+
+    #[test]
+    fn test_clamp() {
+        let clamp = || NumericOp::Clamp(2.into(), 5.into());
+        assert_eq!(eval(Data::UInt(1), clamp()).unwrap(), Data::UInt(2));
+        assert_eq!(eval(Data::UInt(3), clamp()).unwrap(), Data::UInt(3));
+        assert_eq!(eval(Data::UInt(9), clamp()).unwrap(), Data::UInt(5));
+    }
+
+    #[test]
+    fn test_clamp_invalid_bounds() {
+        assert!(matches!(
+            eval(Data::UInt(3), NumericOp::Clamp(5.into(), 2.into())),
+            Err(NameError::InvalidBounds(Data::UInt(5), Data::UInt(2)))
+        ));
+    }
+}
@@ -0,0 +1,28 @@
+use crate::iter::*;
+
+pub struct OnChunkReads<R: Reads, F: Fn(usize) + Send + Sync> {
+    reads: R,
+    func: F,
+}
+
+impl<R: Reads, F: Fn(usize) + Send + Sync> OnChunkReads<R, F> {
+    pub fn new(reads: R, func: F) -> Self {
+        Self { reads, func }
+    }
+}
+
+impl<R: Reads, F: Fn(usize) + Send + Sync> Reads for OnChunkReads<R, F> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let reads = self.reads.next_chunk()?;
+
+        if !reads.is_empty() {
+            (self.func)(reads.len());
+        }
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
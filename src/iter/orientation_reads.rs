@@ -0,0 +1,140 @@
+use crate::iter::*;
+use crate::seq_utils::reverse_complement;
+
+pub struct OrientationReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label1: Label,
+    label2: Label,
+    attr: Option<Attr>,
+    min_overlap: usize,
+    identity: f64,
+}
+
+impl<R: Reads> OrientationReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        min_overlap: usize,
+        identity: f64,
+    ) -> Self {
+        transform_expr.check_size(2, 1, "detecting the orientation of paired reads");
+        assert!(
+            min_overlap >= 1,
+            "Minimum overlap must be greater than zero when detecting the orientation of paired reads"
+        );
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label1: transform_expr.before()[0].clone(),
+            label2: transform_expr.before()[1].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when detecting the orientation of paired reads"),
+            }),
+            min_overlap,
+            identity,
+        }
+    }
+}
+
+impl<R: Reads> Reads for OrientationReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "detecting the orientation of paired reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let seq1 = read
+                .substring(self.label1.str_type, self.label1.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "detecting the orientation of paired reads",
+                })?;
+            let seq2 = read
+                .substring(self.label2.str_type, self.label2.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "detecting the orientation of paired reads",
+                })?;
+
+            let data = match orientation(seq1, seq2, self.min_overlap, self.identity) {
+                Some(o) => Data::Bytes(o.to_vec()),
+                None => Data::Bool(false),
+            };
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| {
+                    panic!("Error detecting the orientation of paired reads: {e}")
+                }) = data;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Classify the orientation of two mates by the longest overlap between them.
+///
+/// Ties are broken in the order `FR`, `RF`, `FF`.
+fn orientation(
+    seq1: &[u8],
+    seq2: &[u8],
+    min_overlap: usize,
+    identity: f64,
+) -> Option<&'static [u8]> {
+    let rc2 = reverse_complement(seq2);
+
+    [
+        // the end of seq1 overlaps the reverse complement of seq2
+        (b"FR", longest_overlap(seq1, &rc2, min_overlap, identity)),
+        // the reverse complement of seq2 overlaps the start of seq1
+        (b"RF", longest_overlap(&rc2, seq1, min_overlap, identity)),
+        // both mates are on the same strand
+        (b"FF", longest_overlap(seq1, seq2, min_overlap, identity)),
+    ]
+    .into_iter()
+    .filter_map(|(o, len)| len.map(|len| (o, len)))
+    .rev()
+    .max_by_key(|&(_, len)| len)
+    .map(|(o, _)| o.as_slice())
+}
+
+/// Find the longest suffix of `a` that matches a prefix of `b` with at least the identity threshold.
+fn longest_overlap(a: &[u8], b: &[u8], min_overlap: usize, identity: f64) -> Option<usize> {
+    let max_overlap = a.len().min(b.len());
+
+    (min_overlap..=max_overlap).rev().find(|&len| {
+        let matches = a[a.len() - len..]
+            .iter()
+            .zip(&b[..len])
+            .filter(|(c1, c2)| c1.eq_ignore_ascii_case(c2))
+            .count();
+        (matches as f64) >= identity * (len as f64)
+    })
+}
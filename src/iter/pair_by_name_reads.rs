@@ -0,0 +1,135 @@
+use rustc_hash::FxHashMap;
+
+use std::collections::hash_map::Entry;
+
+use std::sync::Mutex;
+
+use crate::iter::*;
+
+pub struct PairByNameReads<R1: Reads, R2: Reads> {
+    reads1: R1,
+    reads2: R2,
+    unmatched: Mutex<Unmatched>,
+}
+
+#[derive(Default)]
+struct Unmatched {
+    reads1: FxHashMap<Vec<u8>, Read>,
+    reads2: FxHashMap<Vec<u8>, Read>,
+}
+
+impl<R1: Reads, R2: Reads> PairByNameReads<R1, R2> {
+    pub fn new(reads1: R1, reads2: R2) -> Self {
+        Self {
+            reads1,
+            reads2,
+            unmatched: Mutex::new(Unmatched::default()),
+        }
+    }
+}
+
+impl<R1: Reads, R2: Reads> Reads for PairByNameReads<R1, R2> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        // hold the lock while reading chunks so reads are never matched by two threads
+        let mut unmatched = self.unmatched.lock().unwrap();
+
+        loop {
+            let chunk1 = self.reads1.next_chunk()?;
+            let chunk2 = self.reads2.next_chunk()?;
+
+            if chunk1.is_empty() && chunk2.is_empty() {
+                if let Some(read) = unmatched
+                    .reads1
+                    .values()
+                    .chain(unmatched.reads2.values())
+                    .next()
+                {
+                    return Err(Error::UnpairedRead(format!(
+                        "\"{}\" after pairing by name",
+                        utf8(read.to_fastq1().0)
+                    )));
+                }
+
+                return Ok(Vec::new());
+            }
+
+            let mut res = Vec::with_capacity(chunk1.len().max(chunk2.len()));
+
+            for read in chunk1 {
+                let key = pair_name(read.to_fastq1().0).to_owned();
+
+                if let Some(mate) = unmatched.reads2.remove(&key) {
+                    res.push(pair(read, mate)?);
+                } else {
+                    buffer_unmatched(&mut unmatched.reads1, key, read)?;
+                }
+            }
+
+            for read in chunk2 {
+                let key = pair_name(read.to_fastq1().0).to_owned();
+
+                if let Some(mate) = unmatched.reads1.remove(&key) {
+                    res.push(pair(mate, read)?);
+                } else {
+                    buffer_unmatched(&mut unmatched.reads2, key, read)?;
+                }
+            }
+
+            // an empty chunk means that there are no more reads, so only return once some reads
+            // are paired
+            if !res.is_empty() {
+                return Ok(res);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads1.finish()?;
+        self.reads2.finish()
+    }
+}
+
+fn pair(mut read: Read, mate: Read) -> Result<Read> {
+    match read.pair(mate) {
+        Ok(()) => Ok(read),
+        Err(e) => Err(Error::NameError {
+            source: e,
+            read,
+            context: "pairing reads by name",
+        }),
+    }
+}
+
+/// Buffer a read until its mate is found, without overwriting an earlier read with the same name.
+fn buffer_unmatched(
+    unmatched: &mut FxHashMap<Vec<u8>, Read>,
+    key: Vec<u8>,
+    read: Read,
+) -> Result<()> {
+    match unmatched.entry(key) {
+        Entry::Occupied(e) => Err(Error::NameError {
+            source: NameError::Duplicate(Name::Literal(e.key().clone())),
+            read,
+            context: "pairing reads by name",
+        }),
+        Entry::Vacant(e) => {
+            e.insert(read);
+            Ok(())
+        }
+    }
+}
+
+/// Name used for pairing, which is the name up to the first whitespace without any `/1` or `/2`
+/// mate suffix.
+fn pair_name(name: &[u8]) -> &[u8] {
+    let end = name
+        .iter()
+        .position(|c| c.is_ascii_whitespace())
+        .unwrap_or(name.len());
+    let name = &name[..end];
+
+    match name {
+        [rest @ .., b'/', b'1' | b'2'] => rest,
+        _ => name,
+    }
+}
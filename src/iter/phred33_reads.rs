@@ -0,0 +1,198 @@
+use crate::inline_string::*;
+use crate::iter::*;
+
+use std::sync::OnceLock;
+
+pub struct Phred33Reads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    str_types: Vec<StrType>,
+    offset: Option<u8>,
+    detected_range: OnceLock<(u8, u8)>,
+}
+
+impl<R: Reads> Phred33Reads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        str_types: Vec<StrType>,
+        offset: Option<u8>,
+    ) -> Self {
+        if let Some(offset) = offset {
+            assert!(offset >= b'!', "Quality score offset must be at least 33");
+        }
+
+        let required_names = str_types
+            .iter()
+            .map(|&str_type| {
+                LabelOrAttr::Label(Label {
+                    str_type,
+                    label: InlineString::new(b"*"),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(required_names),
+            str_types,
+            offset,
+            detected_range: OnceLock::new(),
+        }
+    }
+
+    /// Get the quality score offset to use for a chunk of reads.
+    ///
+    /// Unless the offset is specified, it is detected once from the selected reads in the first
+    /// chunk with quality scores and then used for all later reads.
+    fn offset(&self, reads: &[Read]) -> u8 {
+        if let Some(offset) = self.offset {
+            return offset;
+        }
+
+        if let Some(&(min, max)) = self.detected_range.get() {
+            return detect_offset(min, max);
+        }
+
+        let (min, max) = reads
+            .iter()
+            .filter(|read| self.selector_expr.matches(read).unwrap_or(false))
+            .map(|read| self.qual_range(read))
+            .fold((u8::MAX, u8::MIN), |(min, max), (a, b)| {
+                (min.min(a), max.max(b))
+            });
+
+        // wait for a chunk with quality scores before deciding
+        if min > max {
+            return b'!';
+        }
+
+        let &(min, max) = self.detected_range.get_or_init(|| (min, max));
+        detect_offset(min, max)
+    }
+
+    /// Range of the quality score characters of a read, which is empty if there are none.
+    fn qual_range(&self, read: &Read) -> (u8, u8) {
+        self.str_types
+            .iter()
+            .filter_map(|&str_type| read.str_mappings(str_type)?.qual())
+            .flatten()
+            .fold((u8::MAX, u8::MIN), |(min, max), &q| {
+                (min.min(q), max.max(q))
+            })
+    }
+
+    /// Check that a read does not contradict the detected offset.
+    fn check_detected_offset(&self, read: &Read, offset: u8) -> std::result::Result<(), NameError> {
+        let Some(&(detected_min, detected_max)) = self.detected_range.get() else {
+            return Ok(());
+        };
+
+        let (min, max) = self.qual_range(read);
+
+        if min > max || detect_offset(detected_min.min(min), detected_max.max(max)) == offset {
+            return Ok(());
+        }
+
+        let q = if min < detected_min { min } else { max };
+        Err(NameError::InconsistentQual(offset, q as char))
+    }
+}
+
+impl<R: Reads> Reads for Phred33Reads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+        let offset = self.offset(&reads);
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "converting quality scores to phred+33",
+                })?)
+            {
+                return Ok(());
+            }
+
+            if self.offset.is_none() {
+                self.check_detected_offset(read, offset)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "converting quality scores to phred+33",
+                    })?;
+            }
+
+            for &str_type in &self.str_types {
+                let qual = read.str_mappings_mut(str_type).unwrap().qual_mut();
+                let Some(qual) = qual else {
+                    continue;
+                };
+
+                let shift = offset - b'!';
+                qual.iter_mut()
+                    .for_each(|q| *q = q.saturating_sub(shift).max(b'!'));
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Guess the quality score offset from the range of quality score characters.
+///
+/// Characters below `;` only occur with phred+33 and characters above `K` only occur with phred+64.
+/// Ambiguous quality scores are assumed to be phred+33.
+fn detect_offset(min: u8, max: u8) -> u8 {
+    if min >= b';' && max > b'K' {
+        b'@'
+    } else {
+        b'!'
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::*;
+    use crate::*;
+
+    #[test]
+    fn test_phred33_detect_selected() {
+        let reads = iter_fastq1_bytes(b"@a\nAAAA\n+\nhhhh\n@b\nAAA\n+\n!!!\n")
+            .unwrap()
+            .length_in_bounds(sel!(), tr!(seq1.* -> seq1.*.keep), 4..=4)
+            .to_phred33(sel!(seq1.*.keep), [StrType::Seq1], None)
+            .run_collect_reads()
+            .unwrap();
+        assert_eq!(reads[0].to_fastq1().2, b"IIII");
+        assert_eq!(reads[1].to_fastq1().2, b"!!!");
+    }
+
+    #[test]
+    fn test_phred33_inconsistent() {
+        // the first chunk has 256 reads
+        let mut bytes = b"@a\nAAAA\n+\nhhhh\n".repeat(256);
+        bytes.extend_from_slice(b"@b\nAAAA\n+\n!!!!\n");
+
+        let res = iter_fastq1_bytes(&bytes)
+            .unwrap()
+            .to_phred33(sel!(), [StrType::Seq1], None)
+            .run_collect_reads();
+        assert!(matches!(
+            res,
+            Err(Error::NameError {
+                source: NameError::InconsistentQual(b'@', '!'),
+                ..
+            })
+        ));
+    }
+}
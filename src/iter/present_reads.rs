@@ -0,0 +1,68 @@
+use crate::iter::*;
+
+pub struct PresentReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    names: Vec<LabelOrAttr>,
+    attr: Attr,
+    kind: PresentKind,
+}
+
+impl<R: Reads> PresentReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        names: Vec<LabelOrAttr>,
+        attr: Attr,
+        kind: PresentKind,
+    ) -> Self {
+        Self {
+            reads,
+            selector_expr,
+            names,
+            attr,
+            kind,
+        }
+    }
+}
+
+impl<R: Reads> Reads for PresentReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "checking present labels and attributes",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let mut present = self.names.iter().map(|n| n.exists(read));
+            let data = match self.kind {
+                PresentKind::All => Data::Bool(present.all(|p| p)),
+                PresentKind::Any => Data::Bool(present.any(|p| p)),
+                PresentKind::Num => Data::UInt(present.filter(|&p| p).count()),
+            };
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(self.attr.str_type, self.attr.label, self.attr.attr)
+                .unwrap_or_else(|e| panic!("Error checking present labels and attributes: {e}")) =
+                data;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,103 @@
+use crate::iter::*;
+
+/// Reads that are split into pass and fail output files by quality.
+pub type QualSplitReads<R> = CollectFastqReads<CollectFastqReads<QualPassReads<R>>>;
+
+pub struct QualPassReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Attr,
+    threshold: usize,
+    stat: QualStat,
+}
+
+impl<R: Reads> QualPassReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        threshold: usize,
+        stat: QualStat,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "splitting reads by quality");
+        transform_expr.check_same_str_type("splitting reads by quality");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: match transform_expr.after()[0].clone() {
+                Some(LabelOrAttr::Attr(a)) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when splitting reads by quality"),
+            },
+            threshold,
+            stat,
+        }
+    }
+
+    /// Selector expressions for the reads that pass and the reads that fail.
+    pub(crate) fn split_selector_exprs(&self) -> (SelectorExpr, SelectorExpr) {
+        (
+            self.selector_expr
+                .clone()
+                .with_attr(self.attr.clone(), true),
+            self.selector_expr
+                .clone()
+                .with_attr(self.attr.clone(), false),
+        )
+    }
+}
+
+pub(crate) fn parse_qual_split_file_expr(file_expr: &str) -> FormatExpr {
+    FormatExpr::new(file_expr.as_bytes()).unwrap_or_else(|e| {
+        panic!("Error in parsing format expression for the qual_split operation: {e}")
+    })
+}
+
+impl<R: Reads> Reads for QualPassReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "splitting reads by quality",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let qual = read
+                .substring_qual(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "splitting reads by quality",
+                })?;
+
+            // strings without quality scores always fail
+            let pass = qual
+                .map(|q| self.stat.get(q) >= self.threshold)
+                .unwrap_or(false);
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(self.attr.str_type, self.attr.label, self.attr.attr)
+                .unwrap_or_else(|e| panic!("Error splitting reads by quality: {e}")) =
+                Data::Bool(pass);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,110 @@
+use crate::iter::*;
+
+pub struct QualWeightedGcReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    soft_mask: bool,
+}
+
+impl<R: Reads> QualWeightedGcReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, transform_expr: TransformExpr) -> Self {
+        transform_expr.check_size(1, 1, "computing quality-weighted GC content");
+        transform_expr.check_same_str_type("computing quality-weighted GC content");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when computing quality-weighted GC content"),
+            }),
+            soft_mask: false,
+        }
+    }
+
+    /// Skip lowercase (soft-masked) bases when computing the GC content.
+    pub fn with_soft_mask(mut self) -> Self {
+        self.soft_mask = true;
+        self
+    }
+}
+
+impl<R: Reads> Reads for QualWeightedGcReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing quality-weighted GC content",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let gc = qual_weighted_gc(read, &self.label, self.soft_mask).map_err(|e| {
+                Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing quality-weighted GC content",
+                }
+            })?;
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error computing quality-weighted GC content: {e}")) =
+                Data::Float(gc);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Compute the fraction of G/C bases, where each base is weighted by the probability that it is
+/// correct according to its quality score.
+///
+/// Lowercase bases are skipped if `soft_mask` is true.
+fn qual_weighted_gc(
+    read: &Read,
+    label: &Label,
+    soft_mask: bool,
+) -> std::result::Result<f64, NameError> {
+    let (seq, qual) = read.substring_with_qual(label.str_type, label.label)?;
+
+    let mut gc = 0.0;
+    let mut total = 0.0;
+
+    for (&c, &q) in seq.iter().zip(qual) {
+        if soft_mask && c.is_ascii_lowercase() {
+            continue;
+        }
+
+        let q = q.saturating_sub(b'!') as f64;
+        let weight = 1.0 - 10.0f64.powf(-q / 10.0);
+
+        if matches!(c, b'G' | b'C' | b'g' | b'c') {
+            gc += weight;
+        }
+        total += weight;
+    }
+
+    Ok(if total > 0.0 { gc / total } else { 0.0 })
+}
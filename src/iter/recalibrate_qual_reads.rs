@@ -0,0 +1,179 @@
+use crate::iter::*;
+
+pub struct RecalibrateQualReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    labels: Vec<Label>,
+    table: RecalibrationTable,
+}
+
+/// Table for remapping quality scores based on the position of each base and its original
+/// quality score.
+///
+/// Quality scores are Phred scores without the +33 offset. Positions past the end of the table
+/// use the last position in the table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecalibrationTable {
+    table: Vec<[u8; NUM_QUALS]>,
+}
+
+const NUM_QUALS: usize = (b'~' - b'!') as usize + 1;
+
+impl RecalibrationTable {
+    /// Create a table with `num_positions` positions that maps every quality score to itself.
+    pub fn new(num_positions: usize) -> Self {
+        assert!(
+            num_positions > 0,
+            "Expected at least one position in the recalibration table"
+        );
+
+        Self {
+            table: vec![identity_row(); num_positions],
+        }
+    }
+
+    /// Load a table from a TSV file.
+    ///
+    /// Each line has a position, an original quality score, and a new quality score, like
+    /// `0\t30\t28`. Empty lines and lines starting with `#` are skipped. Unspecified entries map
+    /// the quality score to itself.
+    pub fn from_file(file: impl AsRef<str>) -> Result<Self> {
+        let file = file.as_ref();
+        let contents = std::fs::read_to_string(file).map_err(|e| Error::FileIo {
+            file: file.to_owned(),
+            source: Box::new(e),
+        })?;
+
+        let mut entries = Vec::new();
+
+        for line in contents.lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parse_err = |reason| Error::Parse {
+                string: line.to_owned(),
+                context: file.to_owned(),
+                reason,
+            };
+            let fields = line.split('\t').map(|f| f.trim()).collect::<Vec<_>>();
+
+            let [pos, qual, new_qual] = fields[..] else {
+                return Err(parse_err("expected position, quality, and new quality"));
+            };
+            let pos = pos
+                .parse::<usize>()
+                .map_err(|_| parse_err("invalid position"))?;
+            let qual = qual
+                .parse::<u8>()
+                .ok()
+                .filter(|&q| (q as usize) < NUM_QUALS)
+                .ok_or_else(|| parse_err("invalid quality score"))?;
+            let new_qual = new_qual
+                .parse::<u8>()
+                .ok()
+                .filter(|&q| (q as usize) < NUM_QUALS)
+                .ok_or_else(|| parse_err("invalid quality score"))?;
+
+            entries.push((pos, qual, new_qual));
+        }
+
+        let num_positions = entries.iter().map(|&(p, _, _)| p + 1).max().unwrap_or(1);
+        let mut res = Self::new(num_positions);
+        entries
+            .into_iter()
+            .for_each(|(pos, qual, new_qual)| res.set(pos, qual, new_qual));
+        Ok(res)
+    }
+
+    /// Set the new quality score for an original quality score at a position.
+    ///
+    /// The table is extended with identity mappings if the position is past the end of the table.
+    pub fn set(&mut self, pos: usize, qual: u8, new_qual: u8) {
+        assert!(
+            (qual as usize) < NUM_QUALS && (new_qual as usize) < NUM_QUALS,
+            "Quality scores must be at most {} in the recalibration table",
+            NUM_QUALS - 1
+        );
+
+        if pos >= self.table.len() {
+            self.table.resize(pos + 1, identity_row());
+        }
+
+        self.table[pos][qual as usize] = new_qual;
+    }
+
+    /// Get the new quality score for an original quality score at a position.
+    pub fn get(&self, pos: usize, qual: u8) -> u8 {
+        let row = &self.table[pos.min(self.table.len() - 1)];
+        row[(qual as usize).min(NUM_QUALS - 1)]
+    }
+}
+
+fn identity_row() -> [u8; NUM_QUALS] {
+    let mut row = [0u8; NUM_QUALS];
+    row.iter_mut().enumerate().for_each(|(i, q)| *q = i as u8);
+    row
+}
+
+impl<R: Reads> RecalibrateQualReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        labels: Vec<Label>,
+        table: RecalibrationTable,
+    ) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require(labels.iter().cloned().map(LabelOrAttr::Label)),
+            labels,
+            table,
+        }
+    }
+}
+
+impl<R: Reads> Reads for RecalibrateQualReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "recalibrating quality scores",
+                })?)
+            {
+                return Ok(());
+            }
+
+            self.labels
+                .iter()
+                .try_for_each(|l| {
+                    read.substring_qual_mut(l.str_type, l.label)?
+                        .ok_or(NameError::NoQual(Name::StrType(l.str_type)))?
+                        .iter_mut()
+                        .enumerate()
+                        .for_each(|(i, q)| *q = b'!' + self.table.get(i, q.saturating_sub(b'!')));
+                    Ok(())
+                })
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "recalibrating quality scores",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::inline_string::InlineString;
+use crate::iter::*;
+
+pub struct RenumberReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    prefix: Vec<u8>,
+    idx: AtomicUsize,
+}
+
+impl<R: Reads> RenumberReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, prefix: Vec<u8>) -> Self {
+        Self {
+            reads,
+            selector_expr,
+            prefix,
+            idx: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<R: Reads> Reads for RenumberReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        let mut selected = Vec::with_capacity(reads.len());
+        for read in &reads {
+            selected.push(
+                self.selector_expr
+                    .matches(read)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "renumbering reads",
+                    })?,
+            );
+        }
+
+        // reserve a contiguous range of numbers for the selected reads in this chunk
+        let num_selected = selected.iter().filter(|&&s| s).count();
+        let mut idx = self.idx.fetch_add(num_selected, Ordering::Relaxed);
+
+        for (read, selected) in reads.iter_mut().zip(selected) {
+            if !selected {
+                continue;
+            }
+
+            let mut name = self.prefix.clone();
+            name.extend_from_slice(idx.to_string().as_bytes());
+            idx += 1;
+
+            for str_type in [StrType::Name1, StrType::Name2] {
+                if read.str_mappings(str_type).is_none() {
+                    continue;
+                }
+
+                read.set(str_type, InlineString::new(b"*"), &name, None)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "renumbering reads",
+                    })?;
+            }
+        }
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
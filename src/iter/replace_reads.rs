@@ -0,0 +1,136 @@
+use memchr::memmem;
+
+use crate::expr::UNKNOWN_QUAL;
+use crate::iter::*;
+
+pub struct ReplaceReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    labels: Vec<Label>,
+    from: Vec<u8>,
+    to: Vec<u8>,
+}
+
+impl<R: Reads> ReplaceReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        labels: Vec<Label>,
+        from: Vec<u8>,
+        to: Vec<u8>,
+    ) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require(labels.iter().cloned().map(LabelOrAttr::Label)),
+            labels,
+            from,
+            to,
+        }
+    }
+
+    /// Replace in one mapping, or do nothing if there are no occurrences.
+    fn replace(&self, read: &mut Read, label: &Label) -> std::result::Result<(), NameError> {
+        // an empty pattern would match everywhere
+        if self.from.is_empty() {
+            return Ok(());
+        }
+
+        let string = read.substring(label.str_type, label.label)?;
+        let qual = read.substring_qual(label.str_type, label.label)?;
+
+        let mut new_str = Vec::with_capacity(string.len());
+        let mut new_qual = qual.map(|_| Vec::with_capacity(string.len()));
+        let mut start = 0;
+
+        while let Some(i) = memmem::find(&string[start..], &self.from) {
+            let i = start + i;
+            new_str.extend_from_slice(&string[start..i]);
+            new_str.extend_from_slice(&self.to);
+
+            if let (Some(new_qual), Some(qual)) = (&mut new_qual, qual) {
+                new_qual.extend_from_slice(&qual[start..i]);
+                new_qual.resize(new_qual.len() + self.to.len(), UNKNOWN_QUAL);
+            }
+
+            start = i + self.from.len();
+        }
+
+        if start == 0 {
+            return Ok(());
+        }
+
+        new_str.extend_from_slice(&string[start..]);
+
+        if let (Some(new_qual), Some(qual)) = (&mut new_qual, qual) {
+            new_qual.extend_from_slice(&qual[start..]);
+        }
+
+        read.set(label.str_type, label.label, &new_str, new_qual.as_deref())
+    }
+}
+
+impl<R: Reads> Reads for ReplaceReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "replacing substrings",
+                })?)
+            {
+                return Ok(());
+            }
+
+            for label in &self.labels {
+                self.replace(read, label).map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "replacing substrings",
+                })?;
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn replace(from: &str, to: &str) -> (Vec<u8>, Vec<u8>) {
+        let reads = iter_fastq1_bytes(b"@read\nAATTAATT\n+\n01234567\n")
+            .unwrap()
+            .replace(sel!(), [label!(seq1.*)], from, to)
+            .run_collect_reads()
+            .unwrap();
+        let (_, seq, qual) = reads[0].to_fastq1();
+        (seq.to_owned(), qual.to_owned())
+    }
+
+    #[test]
+    fn test_replace_qual() {
+        let (seq, qual) = replace("TT", "GGG");
+        assert_eq!(seq, b"AAGGGAAGGG");
+        assert_eq!(qual.len(), seq.len());
+        assert_eq!(qual, b"01III45III");
+    }
+
+    #[test]
+    fn test_replace_empty_from() {
+        let (seq, qual) = replace("", "GGG");
+        assert_eq!(seq, b"AATTAATT");
+        assert_eq!(qual, b"01234567");
+    }
+}
@@ -16,22 +16,17 @@ impl<R: Reads> RetainReads<R> {
 
 impl<R: Reads> Reads for RetainReads<R> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
-        let reads = self.reads.next_chunk()?;
-        let mut res = Vec::new();
-        for read in reads.into_iter() {
-            if self
-                .selector_expr
-                .matches(&read)
+        let mut reads = self.reads.next_chunk()?;
+        try_retain_reads(&mut reads, |read| {
+            self.selector_expr
+                .matches(read)
                 .map_err(|e| Error::NameError {
                     source: e,
                     read: read.clone(),
                     context: "retain reads",
-                })?
-            {
-                res.push(read);
-            }
-        }
-        Ok(res)
+                })
+        })?;
+        Ok(reads)
     }
 
     fn finish(&mut self) -> Result<()> {
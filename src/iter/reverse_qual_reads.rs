@@ -0,0 +1,59 @@
+use crate::iter::*;
+
+pub struct ReverseQualReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    labels: Vec<Label>,
+}
+
+impl<R: Reads> ReverseQualReads<R> {
+    pub fn new(reads: R, selector_expr: SelectorExpr, labels: Vec<Label>) -> Self {
+        Self {
+            reads,
+            selector_expr: selector_expr.require(labels.iter().cloned().map(LabelOrAttr::Label)),
+            labels,
+        }
+    }
+}
+
+impl<R: Reads> Reads for ReverseQualReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "reversing quality scores",
+                })?)
+            {
+                return Ok(());
+            }
+
+            self.labels
+                .iter()
+                .try_for_each(|l| {
+                    read.substring_qual_mut(l.str_type, l.label)?
+                        .ok_or(NameError::NoQual(Name::StrType(l.str_type)))?
+                        .reverse();
+                    Ok(())
+                })
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "reversing quality scores",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
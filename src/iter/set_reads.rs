@@ -4,7 +4,41 @@ pub struct SetReads<R: Reads> {
     reads: R,
     selector_expr: SelectorExpr,
     label_or_attr: LabelOrAttr,
-    format_expr: FormatExpr,
+    value: Value,
+    missing_qual: MissingQual,
+}
+
+/// Format expressions for the new value.
+enum Value {
+    One(FormatExpr),
+    /// Choose the first format expression if the condition matches, otherwise the second.
+    IfElse(SelectorExpr, FormatExpr, FormatExpr),
+    /// Choose the first format expression whose labels and attributes all exist.
+    Coalesce(Vec<FormatExpr>),
+}
+
+impl Value {
+    fn choose(&self, read: &Read) -> std::result::Result<&FormatExpr, NameError> {
+        use Value::*;
+        match self {
+            One(e) => Ok(e),
+            IfElse(cond, then_expr, else_expr) => {
+                if cond.matches(read)? {
+                    Ok(then_expr)
+                } else {
+                    Ok(else_expr)
+                }
+            }
+            Coalesce(exprs) => {
+                let e = exprs
+                    .iter()
+                    .find(|e| e.required_names().iter().all(|n| n.exists(read)))
+                    // formatting the last format expression will error with the missing name
+                    .unwrap_or_else(|| exprs.last().unwrap());
+                Ok(e)
+            }
+        }
+    }
 }
 
 impl<R: Reads> SetReads<R> {
@@ -14,20 +48,95 @@ impl<R: Reads> SetReads<R> {
         label_or_attr: LabelOrAttr,
         format_expr: FormatExpr,
     ) -> Self {
-        Self {
+        let required_names = format_expr.required_names();
+        Self::new_value(
+            reads,
+            selector_expr,
+            label_or_attr,
+            Value::One(format_expr),
+            required_names,
+        )
+    }
+
+    pub fn new_if_else(
+        reads: R,
+        selector_expr: SelectorExpr,
+        label_or_attr: LabelOrAttr,
+        cond_expr: SelectorExpr,
+        then_expr: FormatExpr,
+        else_expr: FormatExpr,
+    ) -> Self {
+        // only the chosen format expression needs to be formatted, so its names are not required
+        Self::new_value(
+            reads,
+            selector_expr,
+            label_or_attr,
+            Value::IfElse(cond_expr, then_expr, else_expr),
+            Vec::new(),
+        )
+    }
+
+    pub fn new_coalesce(
+        reads: R,
+        selector_expr: SelectorExpr,
+        label_or_attr: LabelOrAttr,
+        format_exprs: Vec<FormatExpr>,
+    ) -> Self {
+        assert!(
+            !format_exprs.is_empty(),
+            "Expected at least one format expression when setting reads"
+        );
+
+        // reads are not skipped when only some of the format expressions can be formatted
+        Self::new_value(
             reads,
             selector_expr,
             label_or_attr,
-            format_expr,
+            Value::Coalesce(format_exprs),
+            Vec::new(),
+        )
+    }
+
+    fn new_value(
+        reads: R,
+        selector_expr: SelectorExpr,
+        label_or_attr: LabelOrAttr,
+        value: Value,
+        mut required_names: Vec<LabelOrAttr>,
+    ) -> Self {
+        if let LabelOrAttr::Label(label) = &label_or_attr {
+            required_names.push(label.clone().into());
+        }
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(required_names),
+            label_or_attr,
+            value,
+            missing_qual: MissingQual::Default,
         }
     }
+
+    /// Use a specific quality score character for parts of the format expression that do not
+    /// have quality scores when setting a label, instead of the default.
+    pub fn with_fill_qual(mut self, qual: u8) -> Self {
+        self.missing_qual = MissingQual::Fill(qual);
+        self
+    }
+
+    /// Return an error if any part of the format expression does not have quality scores when
+    /// setting a label, instead of making up quality scores.
+    pub fn with_require_qual(mut self) -> Self {
+        self.missing_qual = MissingQual::Error;
+        self
+    }
 }
 
 impl<R: Reads> Reads for SetReads<R> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let mut reads = self.reads.next_chunk()?;
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(&read)
@@ -37,11 +146,15 @@ impl<R: Reads> Reads for SetReads<R> {
                     context: "setting reads",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
-            let new_str = self
-                .format_expr
+            let format_expr = self.value.choose(read).map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "setting reads",
+            })?;
+            let new_str = format_expr
                 .format(read, false)
                 .map_err(|e| Error::NameError {
                     source: e,
@@ -61,8 +174,8 @@ impl<R: Reads> Reads for SetReads<R> {
 
                     if str_mappings.qual().is_some() {
                         let new_qual =
-                            self.format_expr
-                                .format(read, true)
+                            format_expr
+                                .format_qual(read, self.missing_qual)
                                 .map_err(|e| Error::NameError {
                                     source: e,
                                     read: read.clone(),
@@ -91,7 +204,9 @@ impl<R: Reads> Reads for SetReads<R> {
                         Data::Bytes(new_str);
                 }
             }
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -100,3 +215,48 @@ impl<R: Reads> Reads for SetReads<R> {
         self.reads.finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::inline_string::*;
+    use crate::*;
+
+    fn out(read: &Read) -> Data {
+        read.data(
+            StrType::Seq1,
+            InlineString::new(b"*"),
+            InlineString::new(b"out"),
+        )
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn test_set_if_else_short_circuit() {
+        let reads = iter_fastq1_bytes(b"@read\nACGT\n+\nIIII\n")
+            .unwrap()
+            .length_in_bounds(sel!(), tr!(seq1.* -> seq1.*.short), ..=2)
+            // `seq1.missing` is only used in the branch that is not taken
+            .set_if_else(
+                sel!(),
+                attr!(seq1.*.out),
+                sel!(seq1.*.short),
+                "{seq1.missing}",
+                "{name1.*}_long",
+            )
+            .run_collect_reads()
+            .unwrap();
+        assert_eq!(out(&reads[0]), Data::Bytes(b"read_long".to_vec()));
+    }
+
+    #[test]
+    fn test_set_coalesce_second() {
+        let reads = iter_fastq1_bytes(b"@read\nACGT\n+\nIIII\n")
+            .unwrap()
+            .cut(sel!(), tr!(seq1.* -> seq1.b, _), 2)
+            .set_coalesce(sel!(), attr!(seq1.*.out), ["{seq1.a}", "{seq1.b}"])
+            .run_collect_reads()
+            .unwrap();
+        assert_eq!(out(&reads[0]), Data::Bytes(b"AC".to_vec()));
+    }
+}
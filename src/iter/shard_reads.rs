@@ -0,0 +1,105 @@
+use crate::iter::*;
+
+/// Reads that are written into a fixed number of shards.
+pub type ShardCollectReads<R> = CollectFastqReads<ShardReads<R>>;
+
+pub struct ShardReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Attr,
+    num_shards: usize,
+}
+
+impl<R: Reads> ShardReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        num_shards: usize,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "sharding reads");
+        transform_expr.check_same_str_type("sharding reads");
+        assert!(
+            num_shards > 0,
+            "Expected at least one shard when sharding reads"
+        );
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: match transform_expr.after()[0].clone() {
+                Some(LabelOrAttr::Attr(a)) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when sharding reads"),
+            },
+            num_shards,
+        }
+    }
+
+    pub(crate) fn selector_expr(&self) -> &SelectorExpr {
+        &self.selector_expr
+    }
+
+    /// Output file for each shard, with `{shard}` in `file_expr` replaced by the shard index.
+    pub(crate) fn file_expr(&self, file_expr: &str) -> FormatExpr {
+        assert!(
+            file_expr.contains("{shard}"),
+            "Expected \"{{shard}}\" in the file expression when sharding reads"
+        );
+
+        let file_expr = file_expr.replace(
+            "{shard}",
+            &format!(
+                "{{{}.{}.{}}}",
+                self.attr.str_type, self.attr.label, self.attr.attr
+            ),
+        );
+
+        FormatExpr::new(file_expr.as_bytes()).unwrap_or_else(|e| {
+            panic!("Error in parsing format expression for the shard operation: {e}")
+        })
+    }
+}
+
+impl<R: Reads> Reads for ShardReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "sharding reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let key = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "sharding reads",
+                })?;
+            let shard = (hash_bytes(key) % (self.num_shards as u64)) as usize;
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(self.attr.str_type, self.attr.label, self.attr.attr)
+                .unwrap_or_else(|e| panic!("Error sharding reads: {e}")) = Data::UInt(shard);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,51 @@
+use rand::prelude::*;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::iter::*;
+
+pub struct ShuffleReads<R: Reads> {
+    reads: R,
+    window: usize,
+    seed: u64,
+}
+
+impl<R: Reads> ShuffleReads<R> {
+    pub fn new(reads: R, window: usize, seed: u32) -> Self {
+        assert!(window >= 1, "Shuffle window must be at least one read");
+
+        Self {
+            reads,
+            window,
+            seed: seed as u64,
+        }
+    }
+}
+
+impl<R: Reads> Reads for ShuffleReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = Vec::new();
+
+        // fill up a window of reads
+        while reads.len() < self.window {
+            let chunk = self.reads.next_chunk()?;
+
+            if chunk.is_empty() {
+                break;
+            }
+
+            reads.extend(chunk);
+        }
+
+        // use the index of the read in the seed for determinism
+        let seed = (self.seed << 32)
+            .wrapping_add(reads.first().map(|r| r.first_idx() as u64).unwrap_or(0u64));
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(seed);
+        reads.shuffle(&mut rng);
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,105 @@
+use crate::iter::*;
+
+pub struct SplitIndexReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    new_label1: Option<Label>,
+    new_label2: Option<Label>,
+    i7_len: usize,
+    i5_len: usize,
+}
+
+impl<R: Reads> SplitIndexReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        i7_len: usize,
+        i5_len: usize,
+    ) -> Self {
+        transform_expr.check_size(1, 2, "splitting index reads");
+        transform_expr.check_same_str_type("splitting index reads");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            new_label1: transform_expr.after()[0].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when splitting index reads"),
+            }),
+            new_label2: transform_expr.after()[1].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when splitting index reads"),
+            }),
+            i7_len,
+            i5_len,
+        }
+    }
+}
+
+impl<R: Reads> Reads for SplitIndexReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "splitting index reads",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let mapping = read
+                .mapping(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "splitting index reads",
+                })?;
+            let start = mapping.start;
+
+            if self.i7_len + self.i5_len > mapping.len {
+                return Err(Error::NameError {
+                    source: NameError::OutOfRange(self.i7_len + self.i5_len, mapping.len),
+                    read: read.clone(),
+                    context: "splitting index reads",
+                });
+            }
+
+            let str_mappings = read.str_mappings_mut(self.label.str_type).unwrap();
+            str_mappings
+                .add_mapping(
+                    self.new_label1.as_ref().map(|l| l.label),
+                    start,
+                    self.i7_len,
+                )
+                .and_then(|_| {
+                    str_mappings.add_mapping(
+                        self.new_label2.as_ref().map(|l| l.label),
+                        start + self.i7_len,
+                        self.i5_len,
+                    )
+                })
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "splitting index reads",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,148 @@
+use crate::iter::*;
+
+pub struct SplitOnReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    delimiter: Vec<u8>,
+    match_type: MatchType,
+}
+
+impl<R: Reads> SplitOnReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        delimiter: Vec<u8>,
+        match_type: MatchType,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "splitting reads on a delimiter");
+        transform_expr.check_same_str_type("splitting reads on a delimiter");
+        assert!(
+            matches!(match_type, ExactSearch | HammingSearch(_)),
+            "Expected ExactSearch or HammingSearch as the match type when splitting reads on a delimiter"
+        );
+        assert!(
+            !delimiter.is_empty(),
+            "Expected a non-empty delimiter when splitting reads on a delimiter"
+        );
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when splitting reads on a delimiter"),
+            }),
+            delimiter,
+            match_type,
+        }
+    }
+
+    /// Find the non-empty segments between non-overlapping occurrences of the delimiter.
+    fn segments(&self, s: &[u8]) -> Vec<(usize, usize)> {
+        let d = self.delimiter.len();
+        let threshold = match self.match_type {
+            HammingSearch(t) => t.get(d),
+            _ => d,
+        };
+
+        let mut res = Vec::new();
+        let mut start = 0;
+        let mut i = 0;
+
+        while i + d <= s.len() {
+            if hamming(&s[i..i + d], &self.delimiter, threshold).is_some() {
+                if i > start {
+                    res.push((start, i));
+                }
+                i += d;
+                start = i;
+            } else {
+                i += 1;
+            }
+        }
+
+        if s.len() > start {
+            res.push((start, s.len()));
+        }
+
+        res
+    }
+}
+
+impl<R: Reads> Reads for SplitOnReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        loop {
+            let reads = self.reads.next_chunk()?;
+
+            if reads.is_empty() {
+                return Ok(reads);
+            }
+
+            let mut res = Vec::with_capacity(reads.len());
+
+            for read in reads.into_iter() {
+                if !(self
+                    .selector_expr
+                    .matches(&read)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "splitting reads on a delimiter",
+                    })?)
+                {
+                    res.push(read);
+                    continue;
+                }
+
+                let string = read
+                    .substring(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "splitting reads on a delimiter",
+                    })?;
+                let len = string.len();
+
+                for (i, (start, end)) in self.segments(string).into_iter().enumerate() {
+                    let mut segment = read.clone();
+                    // remove everything before and then everything after the segment
+                    segment
+                        .crop(self.label.str_type, self.label.label, len - start, Right)
+                        .and_then(|_| {
+                            segment.crop(self.label.str_type, self.label.label, end - start, Left)
+                        })
+                        .map_err(|e| Error::NameError {
+                            source: e,
+                            read: read.clone(),
+                            context: "splitting reads on a delimiter",
+                        })?;
+
+                    if let Some(attr) = &self.attr {
+                        // panic to make borrow checker happy
+                        *segment
+                            .data_mut(attr.str_type, attr.label, attr.attr)
+                            .unwrap_or_else(|e| {
+                                panic!("Error splitting reads on a delimiter: {e}")
+                            }) = Data::UInt(i);
+                    }
+
+                    res.push(segment);
+                }
+            }
+
+            // an empty chunk means that there are no more reads, so only return once some reads
+            // are produced
+            if !res.is_empty() {
+                return Ok(res);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
@@ -0,0 +1,208 @@
+use crate::iter::*;
+
+pub struct StringDistReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    other_expr: FormatExpr,
+    kind: Kind,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Hamming,
+    Edit,
+}
+
+impl<R: Reads> StringDistReads<R> {
+    pub fn new_hamming(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        other_expr: FormatExpr,
+    ) -> Self {
+        Self::new(
+            reads,
+            selector_expr,
+            transform_expr,
+            other_expr,
+            Kind::Hamming,
+        )
+    }
+
+    pub fn new_edit(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        other_expr: FormatExpr,
+    ) -> Self {
+        Self::new(reads, selector_expr, transform_expr, other_expr, Kind::Edit)
+    }
+
+    fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        other_expr: FormatExpr,
+        kind: Kind,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "computing the distance between strings");
+        transform_expr.check_same_str_type("computing the distance between strings");
+
+        let mut required_names = transform_expr.required_names();
+        required_names.extend(other_expr.required_names());
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(required_names),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when computing the distance between strings"),
+            }),
+            other_expr,
+            kind,
+        }
+    }
+
+    fn dist(&self, read: &Read) -> std::result::Result<usize, NameError> {
+        let string = read.substring(self.label.str_type, self.label.label)?;
+        let other = self.other_expr.format(read, false)?;
+
+        use Kind::*;
+        match self.kind {
+            Hamming => {
+                if string.len() != other.len() {
+                    return Err(NameError::UnequalLen(string.len(), other.len()));
+                }
+
+                Ok(string.len() - hamming(string, &other, 0).unwrap())
+            }
+            Edit => Ok(edit_dist(string, &other)),
+        }
+    }
+}
+
+impl<R: Reads> Reads for StringDistReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing the distance between strings",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let dist = self.dist(read).map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "computing the distance between strings",
+            })?;
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error computing the distance between strings: {e}")) =
+                Data::UInt(dist);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Levenshtein distance between two strings.
+///
+/// This only uses two rows that are sized to the shorter string.
+fn edit_dist(a: &[u8], b: &[u8]) -> usize {
+    let (a, b) = if a.len() < b.len() { (b, a) } else { (a, b) };
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut curr = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr[0] = i;
+
+        for j in 1..=b.len() {
+            let sub = prev[j - 1] + ((a[i - 1] != b[j - 1]) as usize);
+            curr[j] = sub.min(prev[j] + 1).min(curr[j - 1] + 1);
+        }
+
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inline_string::*;
+    use crate::*;
+
+    fn hamming_dist(other: &str) -> Result<Vec<Read>> {
+        iter_fastq1_bytes(b"@read\nACGT\n+\nIIII\n")
+            .unwrap()
+            .hamming_dist(sel!(), tr!(seq1.* -> seq1.*.dist), other)
+            .run_collect_reads()
+    }
+
+    fn dist(reads: &[Read]) -> usize {
+        reads[0]
+            .data(
+                StrType::Seq1,
+                InlineString::new(b"*"),
+                InlineString::new(b"dist"),
+            )
+            .unwrap()
+            .as_uint()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_hamming_dist() {
+        assert_eq!(dist(&hamming_dist("ACGT").unwrap()), 0);
+        assert_eq!(dist(&hamming_dist("TGCA").unwrap()), 4);
+        assert_eq!(dist(&hamming_dist("AGGT").unwrap()), 1);
+    }
+
+    #[test]
+    fn test_hamming_dist_unequal_len() {
+        assert!(matches!(
+            hamming_dist("ACG"),
+            Err(Error::NameError {
+                source: NameError::UnequalLen(4, 3),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_edit_dist() {
+        assert_eq!(edit_dist(b"ACGT", b"ACGT"), 0);
+        // insertion
+        assert_eq!(edit_dist(b"ACGT", b"ACCGT"), 1);
+        // deletion
+        assert_eq!(edit_dist(b"ACGT", b"AGT"), 1);
+        // substitution
+        assert_eq!(edit_dist(b"ACGT", b"AGGT"), 1);
+        assert_eq!(edit_dist(b"", b"ACG"), 3);
+        assert_eq!(edit_dist(b"kitten", b"sitting"), 3);
+    }
+}
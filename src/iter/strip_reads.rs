@@ -0,0 +1,114 @@
+use crate::iter::*;
+
+pub struct StripReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    new_label: Option<Label>,
+    chars: Vec<u8>,
+    end: Option<End>,
+}
+
+impl<R: Reads> StripReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        chars: Vec<u8>,
+        end: Option<End>,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "stripping characters from mappings");
+        transform_expr.check_same_str_type("stripping characters from mappings");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            new_label: transform_expr.after()[0].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when stripping characters from mappings"),
+            }),
+            chars,
+            end,
+        }
+    }
+}
+
+impl<R: Reads> Reads for StripReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "stripping characters from mappings",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let start = read
+                .mapping(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "stripping characters from mappings",
+                })?
+                .start;
+            let string = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "stripping characters from mappings",
+                })?;
+            let (offset, len) = strip(string, &self.chars, self.end);
+
+            read.str_mappings_mut(self.label.str_type)
+                .unwrap()
+                .add_mapping(
+                    self.new_label.as_ref().map(|l| l.label),
+                    start + offset,
+                    len,
+                )
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "stripping characters from mappings",
+                })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Returns the offset and length of the string with the characters stripped.
+///
+/// If every character is stripped, then the result is empty.
+fn strip(s: &[u8], chars: &[u8], end: Option<End>) -> (usize, usize) {
+    let start = if matches!(end, None | Some(Left)) {
+        s.iter().position(|c| !chars.contains(c)).unwrap_or(s.len())
+    } else {
+        0
+    };
+    let stop = if matches!(end, None | Some(Right)) {
+        s.iter()
+            .rposition(|c| !chars.contains(c))
+            .map(|i| i + 1)
+            .unwrap_or(0)
+    } else {
+        s.len()
+    };
+
+    (start, stop.saturating_sub(start))
+}
@@ -0,0 +1,83 @@
+use crate::iter::*;
+
+pub struct TrailingHighQualReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    attr: Option<Attr>,
+    threshold: u8,
+}
+
+impl<R: Reads> TrailingHighQualReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        threshold: u8,
+    ) -> Self {
+        transform_expr.check_size(1, 1, "counting trailing high quality bases");
+        transform_expr.check_same_str_type("counting trailing high quality bases");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            attr: transform_expr.after()[0].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when counting trailing high quality bases"),
+            }),
+            threshold,
+        }
+    }
+}
+
+impl<R: Reads> Reads for TrailingHighQualReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "counting trailing high quality bases",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let Some(attr) = &self.attr else {
+                return Ok(());
+            };
+
+            let (_, qual) = read
+                .substring_with_qual(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "counting trailing high quality bases",
+                })?;
+            let count = qual
+                .iter()
+                .rev()
+                .take_while(|&&q| q.saturating_sub(b'!') >= self.threshold)
+                .count();
+
+            // panic to make borrow checker happy
+            *read
+                .data_mut(attr.str_type, attr.label, attr.attr)
+                .unwrap_or_else(|e| panic!("Error counting trailing high quality bases: {e}")) =
+                Data::UInt(count);
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
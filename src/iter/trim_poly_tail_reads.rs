@@ -0,0 +1,137 @@
+use crate::iter::*;
+
+pub struct TrimPolyTailReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    new_label1: Option<Label>,
+    new_label2: Option<Label>,
+    attr: Option<Attr>,
+    base: u8,
+    min_len: usize,
+    max_errors: usize,
+}
+
+impl<R: Reads> TrimPolyTailReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        base: u8,
+        min_len: usize,
+        max_errors: usize,
+    ) -> Self {
+        transform_expr.check_size(1, 3, "finding poly tails");
+        transform_expr.check_same_str_type("finding poly tails");
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            new_label1: transform_expr.after()[0].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when finding poly tails"),
+            }),
+            new_label2: transform_expr.after()[1].clone().map(|l| match l {
+                LabelOrAttr::Label(l) => l,
+                _ => panic!("Expected type.label after the \"->\" in the transform expression when finding poly tails"),
+            }),
+            attr: transform_expr.after()[2].clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when finding poly tails"),
+            }),
+            base,
+            min_len,
+            max_errors,
+        }
+    }
+}
+
+impl<R: Reads> Reads for TrimPolyTailReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "finding poly tails",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let string = read
+                .substring(self.label.str_type, self.label.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "finding poly tails",
+                })?;
+            let tail_len = poly_tail_len(string, self.base, self.max_errors);
+            let tail_len = if tail_len >= self.min_len {
+                tail_len
+            } else {
+                0
+            };
+
+            if tail_len > 0 {
+                read.cut(
+                    self.label.str_type,
+                    self.label.label,
+                    self.new_label1.as_ref().map(|l| l.label),
+                    self.new_label2.as_ref().map(|l| l.label),
+                    EndIdx::RightEnd(tail_len),
+                )
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "finding poly tails",
+                })?;
+            }
+
+            if let Some(attr) = &self.attr {
+                // panic to make borrow checker happy
+                *read
+                    .data_mut(attr.str_type, attr.label, attr.attr)
+                    .unwrap_or_else(|e| panic!("Error finding poly tails: {e}")) =
+                    Data::UInt(tail_len);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Find the length of the longest tail of repeated bases at the right end, allowing some
+/// interrupting bases.
+///
+/// The tail always starts with a matching base, so interrupting bases at the start of the tail
+/// are not included.
+fn poly_tail_len(s: &[u8], base: u8, max_errors: usize) -> usize {
+    let mut errors = 0;
+    let mut len = 0;
+
+    for (i, c) in s.iter().rev().enumerate() {
+        if c.eq_ignore_ascii_case(&base) {
+            len = i + 1;
+        } else {
+            errors += 1;
+
+            if errors > max_errors {
+                break;
+            }
+        }
+    }
+
+    len
+}
@@ -0,0 +1,174 @@
+use memchr::memmem;
+
+use crate::iter::*;
+
+pub struct TrimReadThroughReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label1: Label,
+    label2: Label,
+    new_labels1: [Option<Label>; 2],
+    new_labels2: [Option<Label>; 2],
+    adapter1: Vec<u8>,
+    adapter2: Vec<u8>,
+    identity: f64,
+    overlap: f64,
+}
+
+impl<R: Reads> TrimReadThroughReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        adapter1: Vec<u8>,
+        adapter2: Vec<u8>,
+        identity: f64,
+        overlap: f64,
+    ) -> Self {
+        transform_expr.check_size(2, 4, "trimming read-through");
+
+        let label1 = transform_expr.before()[0].clone();
+        let label2 = transform_expr.before()[1].clone();
+        let new_labels = transform_expr
+            .after()
+            .iter()
+            .zip([&label1, &label1, &label2, &label2])
+            .map(|(l, before)| {
+                l.clone().map(|l| match l {
+                    LabelOrAttr::Label(l) if l.str_type == before.str_type => l,
+                    _ => panic!("Expected type.label after the \"->\" in the transform expression when trimming read-through, with the first two labels having the same type as the first mapping and the last two labels having the same type as the second mapping"),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label1,
+            label2,
+            new_labels1: [new_labels[0].clone(), new_labels[1].clone()],
+            new_labels2: [new_labels[2].clone(), new_labels[3].clone()],
+            adapter1,
+            adapter2,
+            identity,
+            overlap,
+        }
+    }
+}
+
+impl<R: Reads> Reads for TrimReadThroughReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+        let mut aligner = None;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "trimming read-through",
+                })?)
+            {
+                return Ok(());
+            }
+
+            let string1 = read
+                .substring(self.label1.str_type, self.label1.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "trimming read-through",
+                })?;
+            let string2 = read
+                .substring(self.label2.str_type, self.label2.label)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "trimming read-through",
+                })?;
+
+            let aligner = aligner.get_or_insert_with(|| {
+                PrefixSuffixAligner::<false>::new(string1.len().max(string2.len()) * 2)
+            });
+            let insert1 = adapter_start(
+                string1,
+                &self.adapter1,
+                aligner,
+                self.identity,
+                self.overlap,
+            );
+            let insert2 = adapter_start(
+                string2,
+                &self.adapter2,
+                aligner,
+                self.identity,
+                self.overlap,
+            );
+
+            // trim both mates to the same insert length if either mate has read-through
+            let insert_len = match (insert1, insert2) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            let cut_idx = insert_len.map(LeftEnd).unwrap_or(RightEnd(0));
+
+            read.cut(
+                self.label1.str_type,
+                self.label1.label,
+                self.new_labels1[0].as_ref().map(|l| l.label),
+                self.new_labels1[1].as_ref().map(|l| l.label),
+                cut_idx,
+            )
+            .and_then(|_| {
+                read.cut(
+                    self.label2.str_type,
+                    self.label2.label,
+                    self.new_labels2[0].as_ref().map(|l| l.label),
+                    self.new_labels2[1].as_ref().map(|l| l.label),
+                    cut_idx,
+                )
+            })
+            .map_err(|e| Error::NameError {
+                source: e,
+                read: read.clone(),
+                context: "trimming read-through",
+            })?;
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+/// Find where the adapter starts in the string.
+///
+/// A full exact copy of the adapter is searched for first, and then a partial adapter at the end
+/// of the string is found with suffix alignment.
+fn adapter_start(
+    string: &[u8],
+    adapter: &[u8],
+    aligner: &mut PrefixSuffixAligner<false>,
+    identity: f64,
+    overlap: f64,
+) -> Option<usize> {
+    if adapter.is_empty() || string.is_empty() {
+        return None;
+    }
+
+    if let Some(i) = memmem::find(string, adapter) {
+        return Some(i);
+    }
+
+    let additional = ((1.0 - identity).max(0.0) * (adapter.len() as f64)).ceil() as usize;
+    let len = string.len().min(adapter.len() + additional);
+    aligner
+        .align(&string[string.len() - len..], adapter, identity, overlap)
+        .map(|(_, start_idx, _)| string.len() - len + start_idx)
+}
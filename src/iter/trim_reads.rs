@@ -10,7 +10,7 @@ impl<R: Reads> TrimReads<R> {
     pub fn new(reads: R, selector_expr: SelectorExpr, labels: Vec<Label>) -> Self {
         Self {
             reads,
-            selector_expr,
+            selector_expr: selector_expr.require(labels.iter().cloned().map(LabelOrAttr::Label)),
             labels,
         }
     }
@@ -20,7 +20,7 @@ impl<R: Reads> Reads for TrimReads<R> {
     fn next_chunk(&self) -> Result<Vec<Read>> {
         let mut reads = self.reads.next_chunk()?;
 
-        for read in reads.iter_mut() {
+        try_for_each_read(&mut reads, |read| {
             if !(self
                 .selector_expr
                 .matches(read)
@@ -30,7 +30,7 @@ impl<R: Reads> Reads for TrimReads<R> {
                     context: "trim reads",
                 })?)
             {
-                continue;
+                return Ok(());
             }
 
             self.labels
@@ -41,7 +41,9 @@ impl<R: Reads> Reads for TrimReads<R> {
                     read: read.clone(),
                     context: "trim reads",
                 })?;
-        }
+
+            Ok(())
+        })?;
 
         Ok(reads)
     }
@@ -0,0 +1,134 @@
+use std::fmt::Write;
+
+use crate::iter::*;
+
+pub struct WindowTrackReads<R: Reads> {
+    reads: R,
+    selector_expr: SelectorExpr,
+    label: Label,
+    gc_attr: Option<Attr>,
+    qual_attr: Option<Attr>,
+    window: usize,
+}
+
+impl<R: Reads> WindowTrackReads<R> {
+    pub fn new(
+        reads: R,
+        selector_expr: SelectorExpr,
+        transform_expr: TransformExpr,
+        window: usize,
+    ) -> Self {
+        transform_expr.check_size(1, 2, "computing window tracks");
+        transform_expr.check_same_str_type("computing window tracks");
+        assert!(window >= 1, "Window size must be greater than zero");
+
+        let mut attrs = transform_expr.after().iter().map(|a| {
+            a.clone().map(|a| match a {
+                LabelOrAttr::Attr(a) => a,
+                _ => panic!("Expected type.label.attr after the \"->\" in the transform expression when computing window tracks"),
+            })
+        });
+
+        Self {
+            reads,
+            selector_expr: selector_expr.require(transform_expr.required_names()),
+            label: transform_expr.before()[0].clone(),
+            gc_attr: attrs.next().unwrap(),
+            qual_attr: attrs.next().unwrap(),
+            window,
+        }
+    }
+}
+
+impl<R: Reads> Reads for WindowTrackReads<R> {
+    fn next_chunk(&self) -> Result<Vec<Read>> {
+        let mut reads = self.reads.next_chunk()?;
+
+        try_for_each_read(&mut reads, |read| {
+            if !(self
+                .selector_expr
+                .matches(read)
+                .map_err(|e| Error::NameError {
+                    source: e,
+                    read: read.clone(),
+                    context: "computing window tracks",
+                })?)
+            {
+                return Ok(());
+            }
+
+            if let Some(attr) = &self.gc_attr {
+                let seq = read
+                    .substring(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "computing window tracks",
+                    })?;
+                let track = json_array(seq.chunks(self.window).map(|w| {
+                    let gc = w
+                        .iter()
+                        .filter(|&&c| matches!(c, b'G' | b'C' | b'g' | b'c'))
+                        .count();
+                    gc as f64 / w.len() as f64
+                }));
+
+                // panic to make borrow checker happy
+                *read
+                    .data_mut(attr.str_type, attr.label, attr.attr)
+                    .unwrap_or_else(|e| panic!("Error computing window tracks: {e}")) =
+                    Data::Bytes(track);
+            }
+
+            if let Some(attr) = &self.qual_attr {
+                let qual = read
+                    .substring_qual(self.label.str_type, self.label.label)
+                    .map_err(|e| Error::NameError {
+                        source: e,
+                        read: read.clone(),
+                        context: "computing window tracks",
+                    })?
+                    .ok_or_else(|| Error::NameError {
+                        source: NameError::NoQual(Name::StrType(self.label.str_type)),
+                        read: read.clone(),
+                        context: "computing window tracks",
+                    })?;
+                let track = json_array(qual.chunks(self.window).map(|w| {
+                    let sum = w
+                        .iter()
+                        .map(|&q| q.saturating_sub(b'!') as usize)
+                        .sum::<usize>();
+                    sum as f64 / w.len() as f64
+                }));
+
+                // panic to make borrow checker happy
+                *read
+                    .data_mut(attr.str_type, attr.label, attr.attr)
+                    .unwrap_or_else(|e| panic!("Error computing window tracks: {e}")) =
+                    Data::Bytes(track);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(reads)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.reads.finish()
+    }
+}
+
+fn json_array(values: impl Iterator<Item = f64>) -> Vec<u8> {
+    let mut res = String::from("[");
+
+    for (i, v) in values.enumerate() {
+        if i > 0 {
+            res.push(',');
+        }
+        write!(res, "{:.3}", v).unwrap();
+    }
+
+    res.push(']');
+    res.into_bytes()
+}
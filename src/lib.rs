@@ -71,6 +71,15 @@
 //! * `sel!()`: select all reads
 //! * `sel!(seq1.adapter)`: select only reads with the `adapter` mapping in its sequence
 //! * `sel!(seq1.adapter & !seq1.*.discard)`: arbitrary boolean expression!
+//! * `sel!(seq1.*.num_matched >= 2)`: compare a numeric attribute with a number using
+//!   `<`, `<=`, `>`, `>=`, `==`, or `!=`
+//! * `sel!(seq1.*.sample != undetermined)`: compare an attribute with `true`, `false`, or bytes
+//!   using `==` or `!=`. The attribute must have the same type as the value
+//!
+//! In addition to the selector expression, operations are only performed on reads that have all
+//! of the inputs of the operation. If the inputs are not present, like when a pattern
+//! was not matched, then the operation will be skipped for that read. Operations that write
+//! reads to files are an exception: they return an error instead of silently dropping reads.
 //!
 //! ## Transform expressions
 //! Transform expressions allow you to specify the names of the inputs and outputs
@@ -82,6 +91,12 @@
 //! and they are similar to Rust's formatting syntax. For example, you can use `"{seq1.a}_{seq1.b}"`
 //! to concatenate the substrings corresponding to mappings `a` and `b`, separated by an
 //! underscore. A string can also be repeated, like `"{'A'; 4}"`, which results in `AAAA`.
+//! A string can be padded on the left to a minimum width, like `"{seq1.*.lane:03}"`,
+//! which pads with zeros, or `"{seq1.*.lane:3}"`, which pads with spaces. Padded quality scores
+//! use a default quality score. The type of an attribute (`bool`, `uint`, `float`, or `bytes`)
+//! can be formatted instead of its value with `"{seq1.*.lane:type}"`, which is useful for
+//! debugging. ASCII letters can be converted to uppercase or lowercase with `"{seq1.a:upper}"` or
+//! `"{seq1.a:lower}"`, which leaves quality scores unchanged.
 //!
 //! Format expressions are useful for rearranging and modifying strings.
 //! They also preserve quality scores, making rearranging regions in a read easy.
@@ -93,8 +108,10 @@ pub mod iter;
 pub mod patterns;
 pub mod read;
 
+mod bgzf;
 mod inline_string;
 mod parse_utils;
+mod seq_utils;
 
 // commonly used functions and types
 
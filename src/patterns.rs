@@ -1,3 +1,4 @@
+use needletail::parse_fastx_file;
 use serde::{Deserialize, Serialize};
 use serde_yaml;
 
@@ -23,6 +24,7 @@ impl Patterns {
                 .into_iter()
                 .map(|v| Pattern {
                     expr: v,
+                    name: None,
                     attrs: Vec::new(),
                 })
                 .collect(),
@@ -57,7 +59,11 @@ impl Patterns {
                         v.to_data()
                     })
                     .collect::<Vec<_>>();
-                Ok(Pattern { expr, attrs })
+                Ok(Pattern {
+                    expr,
+                    name: None,
+                    attrs,
+                })
             })
             .collect::<Result<Vec<_>>>()?;
 
@@ -70,6 +76,44 @@ impl Patterns {
         })
     }
 
+    /// Load patterns from a FASTA file.
+    ///
+    /// The sequence of each record is used as a pattern and the record's header is stored into
+    /// the `pattern_name` attribute of a mapping when the pattern is matched, instead of the
+    /// pattern itself.
+    pub fn from_fasta(file: impl AsRef<str>, pattern_name: impl AsRef<str>) -> Result<Self> {
+        let mut reader = parse_fastx_file(file.as_ref()).map_err(|e| Error::FileIo {
+            file: file.as_ref().to_owned(),
+            source: Box::new(e),
+        })?;
+        let mut patterns = Vec::new();
+
+        while let Some(record) = reader.next() {
+            let record = record.map_err(|e| Error::FileIo {
+                file: file.as_ref().to_owned(),
+                source: Box::new(e),
+            })?;
+            patterns.push(Pattern {
+                expr: FormatExpr::new(&record.seq())?,
+                name: Some(record.id().to_owned()),
+                attrs: Vec::new(),
+            });
+        }
+
+        Ok(Self {
+            pattern_name: Some(InlineString::new(pattern_name.as_ref().as_bytes())),
+            attr_names: Vec::new(),
+            patterns,
+        })
+    }
+
+    /// Set the attribute that the matched pattern's name (or the pattern string if it has
+    /// no name) is stored into.
+    pub fn with_pattern_name(mut self, pattern_name: impl AsRef<str>) -> Self {
+        self.pattern_name = Some(InlineString::new(pattern_name.as_ref().as_bytes()));
+        self
+    }
+
     pub fn pattern_name(&self) -> Option<InlineString> {
         self.pattern_name
     }
@@ -85,6 +129,8 @@ impl Patterns {
 
 pub struct Pattern {
     pub expr: FormatExpr,
+    /// Name that is stored instead of the pattern string when matched.
+    pub name: Option<Vec<u8>>,
     pub attrs: Vec<Data>,
 }
 
@@ -105,6 +151,7 @@ struct PatternSchema {
 enum DataSchema {
     Bool(bool),
     UInt(usize),
+    Float(f64),
     String(String),
 }
 
@@ -113,6 +160,7 @@ impl DataSchema {
         match self {
             DataSchema::Bool(x) => Data::Bool(*x),
             DataSchema::UInt(x) => Data::UInt(*x),
+            DataSchema::Float(x) => Data::Float(*x),
             DataSchema::String(x) => Data::Bytes(x.as_bytes().to_owned()),
         }
     }
@@ -122,16 +122,75 @@ impl StrMappings {
         self.qual.as_ref().map(|q| q.as_slice())
     }
 
+    pub fn qual_mut(&mut self) -> Option<&mut [u8]> {
+        self.qual.as_deref_mut()
+    }
+
     pub fn substring(&self, mapping: &Mapping) -> &[u8] {
         &self.string[mapping.start..mapping.start + mapping.len]
     }
 
+    pub fn substring_mut(&mut self, mapping: &Mapping) -> &mut [u8] {
+        &mut self.string[mapping.start..mapping.start + mapping.len]
+    }
+
     pub fn substring_qual(&self, mapping: &Mapping) -> Option<&[u8]> {
         self.qual
             .as_ref()
             .map(|q| &q[mapping.start..mapping.start + mapping.len])
     }
 
+    pub fn substring_qual_mut(&mut self, mapping: &Mapping) -> Option<&mut [u8]> {
+        self.qual
+            .as_mut()
+            .map(|q| &mut q[mapping.start..mapping.start + mapping.len])
+    }
+
+    fn write_raw_bytes(&self, res: &mut Vec<u8>) {
+        write_len_prefixed(res, &self.string);
+
+        match &self.qual {
+            Some(qual) => {
+                res.push(1);
+                write_len_prefixed(res, qual);
+            }
+            None => res.push(0),
+        }
+
+        res.extend(self.mappings.len().to_le_bytes());
+
+        for m in &self.mappings {
+            write_len_prefixed(res, &m.label.bytes().collect::<Vec<_>>());
+            res.extend(m.start.to_le_bytes());
+            res.extend(m.len.to_le_bytes());
+
+            // sort so the bytes do not depend on the order of the hash map
+            let mut data = m.data.iter().collect::<Vec<_>>();
+            data.sort_unstable_by_key(|&(&k, _)| k);
+            res.extend(data.len().to_le_bytes());
+
+            for (k, v) in data {
+                write_len_prefixed(res, &k.bytes().collect::<Vec<_>>());
+
+                match v {
+                    Data::Bool(b) => res.extend([0, *b as u8]),
+                    Data::UInt(u) => {
+                        res.push(1);
+                        res.extend(u.to_le_bytes());
+                    }
+                    Data::Float(f) => {
+                        res.push(2);
+                        res.extend(f.to_bits().to_le_bytes());
+                    }
+                    Data::Bytes(b) => {
+                        res.push(3);
+                        write_len_prefixed(res, b);
+                    }
+                }
+            }
+        }
+    }
+
     pub fn cut(
         &mut self,
         label: InlineString,
@@ -201,6 +260,93 @@ impl StrMappings {
         Ok(())
     }
 
+    pub fn difference(
+        &mut self,
+        label1: InlineString,
+        label2: InlineString,
+        new_label1: Option<InlineString>,
+        new_label2: Option<InlineString>,
+    ) -> Result<(), NameError> {
+        let mapping1 = self
+            .mapping(label1)
+            .ok_or(NameError::NotInRead(Name::Label(label1)))?;
+        let mapping2 = self
+            .mapping(label2)
+            .ok_or(NameError::NotInRead(Name::Label(label2)))?;
+
+        let ((start1, len1), (start2, len2)) = mapping1.difference_intervals(mapping2);
+        self.add_mapping(new_label1, start1, len1)?;
+        self.add_mapping(new_label2, start2, len2)?;
+
+        Ok(())
+    }
+
+    pub fn gaps(
+        &mut self,
+        labels: &[InlineString],
+        new_labels: &[Option<InlineString>],
+    ) -> Result<(), NameError> {
+        let mut intervals = labels
+            .iter()
+            .map(|&label| {
+                self.mapping(label)
+                    .map(|m| (m.start, m.start + m.len))
+                    .ok_or(NameError::NotInRead(Name::Label(label)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        intervals.sort_unstable();
+        intervals.push((self.string.len(), self.string.len()));
+
+        let mut prev_end = 0;
+
+        for ((start, end), &new_label) in intervals.into_iter().zip(new_labels) {
+            let gap_end = start.max(prev_end);
+            self.add_mapping(new_label, prev_end, gap_end - prev_end)?;
+            prev_end = prev_end.max(end);
+        }
+
+        Ok(())
+    }
+
+    /// Replace mappings with a single mapping that spans all of them, if there are no gaps
+    /// between them.
+    ///
+    /// Nothing is changed if the mappings are not contiguous.
+    pub fn merge(
+        &mut self,
+        labels: &[InlineString],
+        new_label: Option<InlineString>,
+    ) -> Result<(), NameError> {
+        let mut intervals = labels
+            .iter()
+            .map(|&label| {
+                self.mapping(label)
+                    .map(|m| (m.start, m.start + m.len))
+                    .ok_or(NameError::NotInRead(Name::Label(label)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        intervals.sort_unstable();
+
+        let (start, mut end) = intervals[0];
+
+        for &(s, e) in &intervals[1..] {
+            if s > end {
+                return Ok(());
+            }
+            end = end.max(e);
+        }
+
+        // check before removing the merged mappings so nothing is changed on errors
+        if let Some(new_label) = new_label {
+            if !labels.contains(&new_label) && self.mapping(new_label).is_some() {
+                Err(NameError::Duplicate(Name::Label(new_label)))?
+            }
+        }
+
+        self.mappings.retain(|m| !labels.contains(&m.label));
+        self.add_mapping(new_label, start, end - start)
+    }
+
     pub fn set(
         &mut self,
         label: InlineString,
@@ -280,12 +426,62 @@ impl StrMappings {
         Ok(())
     }
 
+    pub fn append(
+        &mut self,
+        new_label: Option<InlineString>,
+        new_str: &[u8],
+        new_qual: u8,
+    ) -> Result<(), NameError> {
+        let start = self.string.len();
+        self.add_mapping(new_label, start, new_str.len())?;
+
+        // the whole string mapping always spans the whole string
+        self.mappings
+            .iter_mut()
+            .filter(|m| m.label.bytes().all(|b| b == b'*'))
+            .for_each(|m| m.len += new_str.len());
+
+        self.string.extend_from_slice(new_str);
+
+        if let Some(qual) = &mut self.qual {
+            qual.extend(new_str.iter().map(|_| new_qual));
+        }
+
+        Ok(())
+    }
+
     pub fn trim(&mut self, label: InlineString) -> Result<(), NameError> {
         let trimmed = self
             .mapping(label)
             .ok_or_else(|| NameError::NotInRead(Name::Label(label)))?
             .clone();
+        self.trim_mapping(&trimmed);
+        Ok(())
+    }
+
+    /// Shorten a mapping to at most `max_len` by trimming the string from the other end, so
+    /// `max_len` characters are kept from the specified end.
+    pub fn crop(&mut self, label: InlineString, max_len: usize, end: End) -> Result<(), NameError> {
+        let (start, len) = {
+            let mapping = self
+                .mapping(label)
+                .ok_or(NameError::NotInRead(Name::Label(label)))?;
+            (mapping.start, mapping.len)
+        };
+
+        if len <= max_len {
+            return Ok(());
+        }
 
+        let trimmed_start = match end {
+            Left => start + max_len,
+            Right => start,
+        };
+        self.trim_mapping(&Mapping::new(label, trimmed_start, len - max_len));
+        Ok(())
+    }
+
+    fn trim_mapping(&mut self, trimmed: &Mapping) {
         self.mappings.iter_mut().for_each(|m| {
             use Intersection::*;
             match trimmed.intersect(m) {
@@ -319,8 +515,6 @@ impl StrMappings {
         if let Some(qual) = &mut self.qual {
             qual.drain(trimmed.start..trimmed.start + trimmed.len);
         }
-
-        Ok(())
     }
 }
 
@@ -338,6 +532,7 @@ pub struct Mapping {
 pub enum Data {
     Bool(bool),
     UInt(usize),
+    Float(f64),
     Bytes(Vec<u8>),
 }
 
@@ -435,6 +630,15 @@ impl Mapping {
         (start, len)
     }
 
+    pub fn difference_intervals(&self, b: &Self) -> ((usize, usize), (usize, usize)) {
+        let a_start = self.start;
+        let a_end = self.start + self.len;
+        let b_start = b.start.clamp(a_start, a_end);
+        let b_end = (b.start + b.len).clamp(a_start, a_end);
+
+        ((a_start, b_start - a_start), (b_end, a_end - b_end))
+    }
+
     pub fn data(&self, attr: InlineString) -> Option<&Data> {
         self.data.get(&attr)
     }
@@ -487,6 +691,52 @@ impl Read {
         }
     }
 
+    /// Create a read from strings of different types, with optional quality scores.
+    ///
+    /// The read must have at least a `name1` and a `seq1` string.
+    pub fn from_strs(
+        strs: Vec<(StrType, Vec<u8>, Option<Vec<u8>>)>,
+        origin: Arc<Origin>,
+        idx: usize,
+    ) -> Self {
+        let str_mappings = strs
+            .into_iter()
+            .map(|(str_type, string, qual)| {
+                let str_mappings = match qual {
+                    Some(qual) => {
+                        StrMappings::new_with_qual(string, qual, Arc::clone(&origin), idx)
+                    }
+                    None => StrMappings::new(string, Arc::clone(&origin), idx),
+                };
+                (str_type, str_mappings)
+            })
+            .collect();
+
+        Self { str_mappings }
+    }
+
+    /// Combine two single-end reads into one paired-end read.
+    ///
+    /// The `name1` and `seq1` strings of `mate` become the `name2` and `seq2` strings of the
+    /// new read. Other strings in `mate` are ignored.
+    pub fn pair(&mut self, mate: Read) -> Result<(), NameError> {
+        for str_type in [StrType::Name2, StrType::Seq2] {
+            if self.str_mappings(str_type).is_some() {
+                return Err(NameError::Duplicate(Name::StrType(str_type)));
+            }
+        }
+
+        for (str_type, str_mappings) in mate.str_mappings {
+            match str_type {
+                StrType::Name1 => self.str_mappings.push((StrType::Name2, str_mappings)),
+                StrType::Seq1 => self.str_mappings.push((StrType::Seq2, str_mappings)),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn to_fastq1(&self) -> (&[u8], &[u8], &[u8]) {
         let name = self.str_mappings(StrType::Name1).unwrap();
         let seq = self.str_mappings(StrType::Seq1).unwrap();
@@ -499,7 +749,9 @@ impl Read {
         let name2 = self
             .str_mappings(StrType::Name2)
             .ok_or_else(|| NameError::NotInRead(Name::StrType(StrType::Name2)))?;
-        let seq2 = self.str_mappings(StrType::Seq2).unwrap();
+        let seq2 = self
+            .str_mappings(StrType::Seq2)
+            .ok_or(NameError::NotInRead(Name::StrType(StrType::Seq2)))?;
         Ok((
             (name1.string(), seq1.string(), seq1.qual().unwrap()),
             (name2.string(), seq2.string(), seq2.qual().unwrap()),
@@ -574,6 +826,21 @@ impl Read {
         Ok(str_mappings.substring(mapping))
     }
 
+    pub fn substring_mut(
+        &mut self,
+        str_type: StrType,
+        label: InlineString,
+    ) -> Result<&mut [u8], NameError> {
+        let str_mappings = self
+            .str_mappings_mut(str_type)
+            .ok_or(NameError::NotInRead(Name::StrType(str_type)))?;
+        let mapping = str_mappings
+            .mapping(label)
+            .ok_or(NameError::NotInRead(Name::Label(label)))?
+            .clone();
+        Ok(str_mappings.substring_mut(&mapping))
+    }
+
     pub fn substring_qual(
         &self,
         str_type: StrType,
@@ -588,6 +855,41 @@ impl Read {
         Ok(str_mappings.substring_qual(mapping))
     }
 
+    /// Get both the substring and its quality scores for a mapping.
+    ///
+    /// This errors if the string does not have quality scores.
+    pub fn substring_with_qual(
+        &self,
+        str_type: StrType,
+        label: InlineString,
+    ) -> Result<(&[u8], &[u8]), NameError> {
+        let str_mappings = self
+            .str_mappings(str_type)
+            .ok_or(NameError::NotInRead(Name::StrType(str_type)))?;
+        let mapping = str_mappings
+            .mapping(label)
+            .ok_or(NameError::NotInRead(Name::Label(label)))?;
+        let qual = str_mappings
+            .substring_qual(mapping)
+            .ok_or(NameError::NoQual(Name::StrType(str_type)))?;
+        Ok((str_mappings.substring(mapping), qual))
+    }
+
+    pub fn substring_qual_mut(
+        &mut self,
+        str_type: StrType,
+        label: InlineString,
+    ) -> Result<Option<&mut [u8]>, NameError> {
+        let str_mappings = self
+            .str_mappings_mut(str_type)
+            .ok_or(NameError::NotInRead(Name::StrType(str_type)))?;
+        let mapping = str_mappings
+            .mapping(label)
+            .ok_or(NameError::NotInRead(Name::Label(label)))?
+            .clone();
+        Ok(str_mappings.substring_qual_mut(&mapping))
+    }
+
     pub fn cut(
         &mut self,
         str_type: StrType,
@@ -625,6 +927,41 @@ impl Read {
             .union(label1, label2, new_label)
     }
 
+    pub fn difference(
+        &mut self,
+        str_type: StrType,
+        label1: InlineString,
+        label2: InlineString,
+        new_label1: Option<InlineString>,
+        new_label2: Option<InlineString>,
+    ) -> Result<(), NameError> {
+        self.str_mappings_mut(str_type)
+            .ok_or(NameError::NotInRead(Name::StrType(str_type)))?
+            .difference(label1, label2, new_label1, new_label2)
+    }
+
+    pub fn gaps(
+        &mut self,
+        str_type: StrType,
+        labels: &[InlineString],
+        new_labels: &[Option<InlineString>],
+    ) -> Result<(), NameError> {
+        self.str_mappings_mut(str_type)
+            .ok_or(NameError::NotInRead(Name::StrType(str_type)))?
+            .gaps(labels, new_labels)
+    }
+
+    pub fn merge(
+        &mut self,
+        str_type: StrType,
+        labels: &[InlineString],
+        new_label: Option<InlineString>,
+    ) -> Result<(), NameError> {
+        self.str_mappings_mut(str_type)
+            .ok_or(NameError::NotInRead(Name::StrType(str_type)))?
+            .merge(labels, new_label)
+    }
+
     pub fn set(
         &mut self,
         str_type: StrType,
@@ -637,15 +974,91 @@ impl Read {
             .set(label, new_str, new_qual)
     }
 
+    /// Append a string to the end of a string type, adding a new mapping for the appended string.
+    ///
+    /// If the string type does not exist, then it is created, with quality scores for sequences.
+    pub fn append(
+        &mut self,
+        str_type: StrType,
+        new_label: Option<InlineString>,
+        new_str: &[u8],
+        new_qual: u8,
+    ) -> Result<(), NameError> {
+        if self.str_mappings(str_type).is_none() {
+            let (origin, idx) = self
+                .str_mappings
+                .first()
+                .map(|(_, s)| (Arc::clone(&s.origin), s.idx))
+                .unwrap();
+            let str_mappings = match str_type {
+                StrType::Name1 | StrType::Name2 => StrMappings::new(Vec::new(), origin, idx),
+                _ => StrMappings::new_with_qual(Vec::new(), Vec::new(), origin, idx),
+            };
+            self.str_mappings.push((str_type, str_mappings));
+        }
+
+        self.str_mappings_mut(str_type)
+            .unwrap()
+            .append(new_label, new_str, new_qual)
+    }
+
     pub fn trim(&mut self, str_type: StrType, label: InlineString) -> Result<(), NameError> {
         self.str_mappings_mut(str_type)
             .ok_or_else(|| NameError::NotInRead(Name::StrType(str_type)))?
             .trim(label)
     }
 
+    pub fn crop(
+        &mut self,
+        str_type: StrType,
+        label: InlineString,
+        max_len: usize,
+        end: End,
+    ) -> Result<(), NameError> {
+        self.str_mappings_mut(str_type)
+            .ok_or(NameError::NotInRead(Name::StrType(str_type)))?
+            .crop(label, max_len, end)
+    }
+
     pub fn first_idx(&self) -> usize {
         self.str_mappings.iter().map(|(_, s)| s.idx).min().unwrap()
     }
+
+    /// Index of the read in its input, where both records of an interleaved paired-end read
+    /// are counted once.
+    pub fn read_idx(&self) -> usize {
+        let idx = self.first_idx();
+
+        match (
+            self.str_mappings(StrType::Name1),
+            self.str_mappings(StrType::Name2),
+        ) {
+            (Some(a), Some(b)) if Arc::ptr_eq(&a.origin, &b.origin) && b.idx == a.idx + 1 => {
+                idx / 2
+            }
+            _ => idx,
+        }
+    }
+
+    /// Serialize the strings, quality scores, mappings, and data of the read.
+    ///
+    /// This is used to hash whole reads, so it does not require the strings to be valid UTF-8 like
+    /// displaying the read.
+    pub(crate) fn raw_bytes(&self) -> Vec<u8> {
+        let mut res = Vec::new();
+
+        for (str_type, str_mappings) in &self.str_mappings {
+            res.push(*str_type as u8);
+            str_mappings.write_raw_bytes(&mut res);
+        }
+
+        res
+    }
+}
+
+fn write_len_prefixed(res: &mut Vec<u8>, b: &[u8]) {
+    res.extend(b.len().to_le_bytes());
+    res.extend_from_slice(b);
 }
 
 impl Data {
@@ -654,6 +1067,7 @@ impl Data {
         match self {
             Bool(x) => *x,
             UInt(x) => *x > 0,
+            Float(x) => *x != 0.0,
             Bytes(x) => !x.is_empty(),
         }
     }
@@ -663,7 +1077,17 @@ impl Data {
         match self {
             Bool(x) => Ok(if *x { 1 } else { 0 }),
             UInt(x) => Ok(*x),
-            Bytes(_) => Err(NameError::Type("bool or uint", self.clone())),
+            Float(_) | Bytes(_) => Err(NameError::Type("bool or uint", self.clone())),
+        }
+    }
+
+    pub fn as_float(&self) -> Result<f64, NameError> {
+        use Data::*;
+        match self {
+            Bool(x) => Ok(if *x { 1.0 } else { 0.0 }),
+            UInt(x) => Ok(*x as f64),
+            Float(x) => Ok(*x),
+            Bytes(_) => Err(NameError::Type("bool, uint, or float", self.clone())),
         }
     }
 
@@ -672,9 +1096,21 @@ impl Data {
         match self {
             Bool(_) => Err(NameError::Type("bytes", self.clone())),
             UInt(_) => Err(NameError::Type("bytes", self.clone())),
+            Float(_) => Err(NameError::Type("bytes", self.clone())),
             Bytes(x) => Ok(x.len()),
         }
     }
+
+    /// Name of the type of the data, like `"uint"` or `"bytes"`.
+    pub fn type_name(&self) -> &'static str {
+        use Data::*;
+        match self {
+            Bool(_) => "bool",
+            UInt(_) => "uint",
+            Float(_) => "float",
+            Bytes(_) => "bytes",
+        }
+    }
 }
 
 impl EndIdx {
@@ -686,6 +1122,20 @@ impl EndIdx {
     }
 }
 
+/// Non-negative indices are from the left end and negative indices are from the right end.
+///
+/// For example, `-4` is the same as `RightEnd(4)`, so cutting at `-4` splits off the last 4
+/// characters. Note that `0` is always `LeftEnd(0)`; use `RightEnd(0)` to refer to the very end.
+impl From<isize> for EndIdx {
+    fn from(idx: isize) -> Self {
+        if idx < 0 {
+            RightEnd(idx.unsigned_abs())
+        } else {
+            LeftEnd(idx as usize)
+        }
+    }
+}
+
 impl fmt::Display for StrMappings {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use colored::Colorize;
@@ -768,6 +1218,7 @@ impl fmt::Display for Data {
         match self {
             Bool(x) => write!(f, "{}", x),
             UInt(x) => write!(f, "{}", x),
+            Float(x) => write!(f, "{}", x),
             Bytes(x) => write!(f, "{}", std::str::from_utf8(x).unwrap()),
         }
     }
@@ -779,6 +1230,7 @@ impl fmt::Debug for Data {
         match self {
             Bool(x) => write!(f, "bool {}", x),
             UInt(x) => write!(f, "uint {}", x),
+            Float(x) => write!(f, "float {}", x),
             Bytes(x) => write!(f, "bytes \"{}\"", std::str::from_utf8(x).unwrap()),
         }
     }
@@ -816,3 +1268,54 @@ impl fmt::Display for StrType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cut(cut_idx: impl Into<EndIdx>) -> (Vec<u8>, Vec<u8>) {
+        let mut read = Read::from_fastq1(b"read", b"ACGT", b"IIII", Arc::new(Origin::Bytes), 0);
+        let (label, left, right) = (
+            InlineString::new(b"*"),
+            InlineString::new(b"left"),
+            InlineString::new(b"right"),
+        );
+        read.cut(
+            StrType::Seq1,
+            label,
+            Some(left),
+            Some(right),
+            cut_idx.into(),
+        )
+        .unwrap();
+        (
+            read.substring(StrType::Seq1, left).unwrap().to_owned(),
+            read.substring(StrType::Seq1, right).unwrap().to_owned(),
+        )
+    }
+
+    #[test]
+    fn test_end_idx_from_isize() {
+        assert_eq!(EndIdx::from(0), LeftEnd(0));
+        assert_eq!(EndIdx::from(3), LeftEnd(3));
+        assert_eq!(EndIdx::from(-1), RightEnd(1));
+        assert_eq!(
+            EndIdx::from(isize::MIN),
+            RightEnd(isize::MIN.unsigned_abs())
+        );
+    }
+
+    #[test]
+    fn test_cut_boundaries() {
+        // `-len` cuts at the very start
+        assert_eq!(cut(-4), (b"".to_vec(), b"ACGT".to_vec()));
+        // indices past the start are clamped
+        assert_eq!(cut(-5), (b"".to_vec(), b"ACGT".to_vec()));
+        // `0` is the left end
+        assert_eq!(cut(0), (b"".to_vec(), b"ACGT".to_vec()));
+        // `RightEnd(0)` is the right end
+        assert_eq!(cut(RightEnd(0)), (b"ACGT".to_vec(), b"".to_vec()));
+        assert_eq!(cut(-1), (b"ACG".to_vec(), b"T".to_vec()));
+        assert_eq!(cut(5), (b"ACGT".to_vec(), b"".to_vec()));
+    }
+}
@@ -0,0 +1,80 @@
+pub fn complement(c: u8) -> u8 {
+    match c {
+        b'A' => b'T',
+        b'T' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'a' => b't',
+        b't' => b'a',
+        b'c' => b'g',
+        b'g' => b'c',
+        _ => c,
+    }
+}
+
+pub fn reverse_complement(s: &[u8]) -> Vec<u8> {
+    s.iter().rev().map(|&c| complement(c)).collect()
+}
+
+/// Translate a nucleotide string to single-letter amino acids using the standard codon table.
+///
+/// Translation stops after the first stop codon, which is translated to `*`. Codons with bases
+/// other than A, C, G, T, or U are translated to `X`, and a trailing partial codon is ignored.
+pub fn translate(s: &[u8]) -> Vec<u8> {
+    // codons are ordered by T, C, A, G for each base
+    const AMINO_ACIDS: &[u8; 64] =
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+    let base_idx = |c: u8| match c.to_ascii_uppercase() {
+        b'T' | b'U' => Some(0),
+        b'C' => Some(1),
+        b'A' => Some(2),
+        b'G' => Some(3),
+        _ => None,
+    };
+
+    let mut res = Vec::with_capacity(s.len() / 3);
+
+    for codon in s.chunks_exact(3) {
+        let idx = codon
+            .iter()
+            .try_fold(0, |acc, &c| base_idx(c).map(|i| acc * 4 + i));
+        let amino_acid = idx.map(|i| AMINO_ACIDS[i]).unwrap_or(b'X');
+        res.push(amino_acid);
+
+        if amino_acid == b'*' {
+            break;
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translate() {
+        // ATG GCC TGG AAA -> M A W K
+        assert_eq!(translate(b"ATGGCCTGGAAA"), b"MAWK");
+        assert_eq!(translate(b"auggccuggaaa"), b"MAWK");
+    }
+
+    #[test]
+    fn test_translate_unknown() {
+        assert_eq!(translate(b"ATGNNNGCN"), b"MXX");
+    }
+
+    #[test]
+    fn test_translate_stop() {
+        assert_eq!(translate(b"ATGTAAGCC"), b"M*");
+        assert_eq!(translate(b"TGA"), b"*");
+    }
+
+    #[test]
+    fn test_translate_partial_codon() {
+        assert_eq!(translate(b"ATGGC"), b"M");
+        assert_eq!(translate(b"AT"), b"");
+    }
+}